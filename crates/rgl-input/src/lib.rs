@@ -3,8 +3,9 @@ use bevy::{
     prelude::*,
 };
 use rgl_registry::{
-    new_registry, RegistryAppExt, RegistryItem, RegistryOneSidedDataCell,
-    RegistryTwoSidedDataCellId2Value,
+    new_registry, ChangableRegistryTwoSidedDataCellId2Value, RegistryAppExt, RegistryData,
+    RegistryDataRead, RegistryIdFilter, RegistryItem, RegistryManyToManyDataCell,
+    RegistryOneSidedDataCell,
 };
 
 pub struct BindingPlugin;
@@ -16,10 +17,12 @@ impl Plugin for BindingPlugin {
     fn build(&self, app: &mut App) {
         app.configure_sets(PreUpdate, BindingSet.after(InputSystem))
             .add_systems(PreUpdate, key_binding_input.in_set(BindingSet))
-            .register_two_sided_data_id2value::<BindingRegistry, &'static str>("bindings")
-            .register_two_sided_data_id2value::<BindingCategoryRegistry, &'static str>(
-                "binding_categories",
-            );
+            .register_name::<BindingRegistry>("bindings")
+            .register_name::<BindingCategoryRegistry>("binding_categories")
+            .register_type::<Key>()
+            .register_type::<DefaultBindingKey>()
+            .register_type::<SetBindingKey>()
+            .register_type::<BindingState>();
     }
 }
 
@@ -28,9 +31,17 @@ new_registry!(BindingRegistry, u16);
 new_registry!(BindingCategoryRegistry, u8);
 
 pub trait BindingAppExt {
+    /// Registers a binding with its own key,
+    /// the keys are stored in [`RegistryTwoSidedDataCellId2Value`](rgl_registry::RegistryTwoSidedDataCellId2Value)
     fn register_binding<I>(&mut self, name: &'static str, default_key: Key) -> &mut Self
     where
         I: RegistryItem<Registry = BindingRegistry>;
+
+    /// Registers a binding, that can share its key with other shared bindings,
+    /// the keys are stored in [`RegistryManyToManyDataCell`]
+    fn register_shared_binding<I>(&mut self, name: &'static str, default_key: Key) -> &mut Self
+    where
+        I: RegistryItem<Registry = BindingRegistry>;
 }
 
 impl BindingAppExt for App {
@@ -38,26 +49,49 @@ impl BindingAppExt for App {
     where
         I: RegistryItem<Registry = BindingRegistry>,
     {
-        self.register_two_sided_data_id2value::<I, &'static str>(name)
+        self.register_name::<I>(name)
             .register_one_sided_data::<I, DefaultBindingKey>(DefaultBindingKey(default_key))
             .register_two_sided_data_id2value::<I, SetBindingKey>(SetBindingKey(default_key))
             .register_one_sided_data::<I, BindingState>(BindingState::default());
         self
     }
+
+    fn register_shared_binding<I>(&mut self, name: &'static str, default_key: Key) -> &mut Self
+    where
+        I: RegistryItem<Registry = BindingRegistry>,
+    {
+        type BindingKeys =
+            ChangableRegistryTwoSidedDataCellId2Value<BindingRegistry, SetBindingKey>;
+
+        // The shared bindings have no value in the cell of the bindings with their own key
+        self.init_resource::<BindingKeys>()
+            .world
+            .resource_mut::<BindingKeys>()
+            .c1
+            .set_optional();
+        self.register_name::<I>(name)
+            .register_one_sided_data::<I, DefaultBindingKey>(DefaultBindingKey(default_key))
+            .register_many_to_many_data::<I, SetBindingKey>(SetBindingKey(default_key))
+            .register_one_sided_data::<I, BindingState>(BindingState::default());
+        self
+    }
 }
 
-#[derive(Clone, Copy, Debug, Hash, PartialEq, Eq)]
+#[derive(Clone, Copy, Debug, Hash, PartialEq, Eq, Reflect)]
 pub enum Key {
     Keyboard(KeyCode),
     Mouse(MouseButton),
 }
 
+#[derive(Reflect)]
 pub struct DefaultBindingKey(pub Key);
 
-#[derive(Hash, Clone, Copy, Debug, PartialEq, Eq)]
+/// The key of the binding, only the bindings registered with
+/// [`BindingAppExt::register_shared_binding`] can share one key
+#[derive(Hash, Clone, Copy, Debug, PartialEq, Eq, Reflect)]
 pub struct SetBindingKey(pub Key);
 
-#[derive(Default, Clone, Copy, Debug, PartialEq, Eq)]
+#[derive(Default, Clone, Copy, Debug, PartialEq, Eq, Reflect)]
 pub struct BindingState(u8);
 
 impl BindingState {
@@ -94,13 +128,57 @@ impl BindingState {
 
 pub type BindingStates = RegistryOneSidedDataCell<BindingRegistry, BindingState>;
 
+/// Checks the states of a category of bindings, e.g. a [`RegistryTagSet`](rgl_registry::RegistryTagSet) of bindings
+pub trait BindingStatesExt {
+    fn any_just_pressed(&self, category: &impl RegistryIdFilter<BindingRegistry>) -> bool;
+
+    fn any_just_released(&self, category: &impl RegistryIdFilter<BindingRegistry>) -> bool;
+
+    fn any_pressed(&self, category: &impl RegistryIdFilter<BindingRegistry>) -> bool;
+}
+
+fn any_state(
+    states: &BindingStates,
+    category: &impl RegistryIdFilter<BindingRegistry>,
+    check: impl Fn(&BindingState) -> bool,
+) -> bool {
+    states
+        .c1
+        .entries()
+        .any(|(id, state)| check(state) && category.contains(&id))
+}
+
+impl BindingStatesExt for BindingStates {
+    fn any_just_pressed(&self, category: &impl RegistryIdFilter<BindingRegistry>) -> bool {
+        any_state(self, category, BindingState::just_pressed)
+    }
+
+    fn any_just_released(&self, category: &impl RegistryIdFilter<BindingRegistry>) -> bool {
+        any_state(self, category, BindingState::just_released)
+    }
+
+    fn any_pressed(&self, category: &impl RegistryIdFilter<BindingRegistry>) -> bool {
+        any_state(self, category, BindingState::pressed)
+    }
+}
+
 fn key_binding_input(
     mut keyboard: EventReader<KeyboardInput>,
     mut mouse: EventReader<MouseButtonInput>,
-    binding_keys: Res<RegistryTwoSidedDataCellId2Value<BindingRegistry, SetBindingKey>>,
+    binding_keys: RegistryData<BindingRegistry, SetBindingKey>,
+    shared_binding_keys: Option<Res<RegistryManyToManyDataCell<BindingRegistry, SetBindingKey>>>,
     mut binding_states: ResMut<BindingStates>,
 ) {
-    binding_states.c1.iter_mut().for_each(BindingState::clear);
+    // Only the written states are marked as changed, so the states are not borrowed mutably every frame
+    let just_changed = binding_states
+        .c1
+        .entries()
+        .filter(|(_, state)| state.just_pressed() || state.just_released())
+        .map(|(id, _)| id)
+        .collect::<Vec<_>>();
+    for id in just_changed {
+        binding_states.value_mut(&id).unwrap().clear();
+    }
     for (key, pressed) in keyboard
         .read()
         .filter_map(|v| {
@@ -113,13 +191,103 @@ fn key_binding_input(
                 .map(|v| (Key::Mouse(v.button), v.state.is_pressed())),
         )
     {
-        if let Some(bid) = binding_keys.id(&SetBindingKey(key)) {
-            let value = binding_states.value_mut(bid).unwrap();
+        let key = SetBindingKey(key);
+        let bids = if binding_keys.is_registered() {
+            binding_keys.id(&key)
+        } else {
+            None
+        };
+        let shared_bids = shared_binding_keys
+            .as_ref()
+            .map_or(&[][..], |keys| keys.ids_of(&key));
+        for bid in bids.iter().chain(shared_bids) {
+            let mut state = *binding_states.value(bid).unwrap();
             if pressed {
-                value.press();
+                state.press();
             } else {
-                value.release();
+                state.release();
+            }
+            if binding_states.value(bid) != Some(&state) {
+                *binding_states.value_mut(bid).unwrap() = state;
             }
         }
     }
 }
+
+#[cfg(test)]
+mod tests {
+    use bevy::input::{ButtonState, InputPlugin};
+    use rgl_registry::{new_registry_items, new_registry_tags, RegistryId, RegistryTagSet};
+
+    use super::*;
+
+    new_registry_items!(BindingRegistry {
+        TestJump,
+        TestFire,
+        TestCrouch
+    });
+    new_registry_tags!(BindingRegistry { TestMovementTag });
+
+    fn press(app: &mut App, key_code: KeyCode, state: ButtonState) {
+        app.world.send_event(KeyboardInput {
+            scan_code: 0,
+            key_code: Some(key_code),
+            state,
+            window: Entity::PLACEHOLDER,
+        });
+        app.update();
+    }
+
+    #[test]
+    fn tag_states() {
+        let mut app = App::new();
+        app.add_plugins((InputPlugin, BindingPlugin))
+            .register_binding::<TestJump>("test_jump", Key::Keyboard(KeyCode::Space))
+            .register_binding::<TestFire>("test_fire", Key::Keyboard(KeyCode::F))
+            .register_binding::<TestCrouch>("test_crouch", Key::Keyboard(KeyCode::C))
+            .add_to_tag::<TestMovementTag, TestJump>()
+            .add_to_tag::<TestMovementTag, TestCrouch>();
+        app.update();
+
+        let tag = app
+            .world
+            .resource::<RegistryTagSet<TestMovementTag>>()
+            .clone();
+        press(&mut app, KeyCode::F, ButtonState::Pressed);
+        let states = app.world.resource::<BindingStates>();
+        assert!(states.any_just_pressed(&RegistryId::new::<TestFire>()));
+        assert!(!states.any_pressed(&tag));
+
+        press(&mut app, KeyCode::C, ButtonState::Pressed);
+        let states = app.world.resource::<BindingStates>();
+        assert!(states.any_just_pressed(&tag));
+        assert!(!states.any_just_pressed(&RegistryId::new::<TestFire>()));
+
+        press(&mut app, KeyCode::C, ButtonState::Released);
+        let states = app.world.resource::<BindingStates>();
+        assert!(states.any_just_released(&tag));
+        assert!(!states.any_pressed(&tag));
+        assert!(states.any_pressed(&RegistryId::new::<TestFire>()));
+    }
+
+    #[test]
+    fn shared_keys() {
+        let mut app = App::new();
+        app.add_plugins((InputPlugin, BindingPlugin))
+            .register_binding::<TestJump>("test_jump", Key::Keyboard(KeyCode::Space))
+            .register_shared_binding::<TestFire>("test_fire", Key::Keyboard(KeyCode::F))
+            .register_shared_binding::<TestCrouch>("test_crouch", Key::Keyboard(KeyCode::F));
+        app.update();
+
+        press(&mut app, KeyCode::F, ButtonState::Pressed);
+        let states = app.world.resource::<BindingStates>();
+        assert!(states.any_pressed(&RegistryId::new::<TestFire>()));
+        assert!(states.any_pressed(&RegistryId::new::<TestCrouch>()));
+        assert!(!states.any_pressed(&RegistryId::new::<TestJump>()));
+
+        press(&mut app, KeyCode::Space, ButtonState::Pressed);
+        let states = app.world.resource::<BindingStates>();
+        assert!(states.any_just_pressed(&RegistryId::new::<TestJump>()));
+        assert!(!states.any_just_pressed(&RegistryId::new::<TestFire>()));
+    }
+}
@@ -0,0 +1,369 @@
+use std::{collections::BTreeMap, fmt, marker::PhantomData};
+
+use bevy::{
+    asset::{io::Reader, AssetLoader, AsyncReadExt, LoadContext},
+    prelude::*,
+    utils::BoxedFuture,
+};
+use rgl_registry::*;
+use serde::{Deserialize, Serialize};
+
+use crate::{DefaultLevel, Level};
+
+/// Maps the characters of a text map to the names of the items, that are registered
+/// in the [`RegistryNameCell`], see [`Level::from_ascii`]
+#[derive(Clone, Debug, Default, PartialEq, Eq, Serialize, Deserialize)]
+pub struct LevelLegend {
+    pub tiles: BTreeMap<char, String>,
+    /// Name of the level kind, [`DefaultLevel`] is used if it is not set
+    pub kind: Option<String>,
+}
+
+impl LevelLegend {
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    pub fn with_tile(mut self, ch: char, name: impl Into<String>) -> Self {
+        self.tiles.insert(ch, name.into());
+        self
+    }
+
+    pub fn with_kind(mut self, name: impl Into<String>) -> Self {
+        self.kind = Some(name.into());
+        self
+    }
+}
+
+#[derive(Debug)]
+pub enum LevelAsciiError {
+    /// The character is not in the legend, the line and the column start from 1
+    UnknownChar {
+        ch: char,
+        line: usize,
+        column: usize,
+    },
+    /// The name of the character in the legend is not registered
+    UnknownTile {
+        ch: char,
+        name: String,
+    },
+    /// The name of the level kind in the legend is not registered
+    UnknownKind(String),
+    /// The line is not as long as the first one
+    UnevenLine {
+        line: usize,
+        len: usize,
+        expected: usize,
+    },
+    Empty,
+    Io(std::io::Error),
+    Utf8(std::string::FromUtf8Error),
+}
+
+impl fmt::Display for LevelAsciiError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            Self::UnknownChar { ch, line, column } => write!(
+                f,
+                "Unknown character {:?} at line {}, column {}",
+                ch, line, column
+            ),
+            Self::UnknownTile { ch, name } => {
+                write!(f, "Tile {} of character {:?} is not registered", name, ch)
+            }
+            Self::UnknownKind(name) => write!(f, "Level kind {} is not registered", name),
+            Self::UnevenLine {
+                line,
+                len,
+                expected,
+            } => write!(
+                f,
+                "Line {} has {} characters, but the first one has {}",
+                line, len, expected
+            ),
+            Self::Empty => write!(f, "Level map is empty"),
+            Self::Io(err) => write!(f, "Couldn't read level map: {}", err),
+            Self::Utf8(err) => write!(f, "Level map is not valid UTF-8: {}", err),
+        }
+    }
+}
+
+impl std::error::Error for LevelAsciiError {}
+
+impl<R: Registry> Level<R> {
+    /// Builds a level from a text map, where every character is a tile of the legend.
+    /// The first line is the first row of the level, like in [`Level::from_tiles`],
+    /// all lines must have the same length and the trailing empty lines are ignored
+    pub fn from_ascii(map: &str, legend: &LevelLegend) -> Result<Self, LevelAsciiError> {
+        let mut lines = map.lines().collect::<Vec<_>>();
+        while lines.last().is_some_and(|line| line.is_empty()) {
+            lines.pop();
+        }
+        let width = match lines.first() {
+            Some(line) if !line.is_empty() => line.chars().count(),
+            _ => return Err(LevelAsciiError::Empty),
+        };
+
+        let tiles = legend
+            .tiles
+            .iter()
+            .map(|(&ch, name)| {
+                RegistryId::<R>::from_name(name)
+                    .map(|id| (ch, id))
+                    .ok_or_else(|| LevelAsciiError::UnknownTile {
+                        ch,
+                        name: name.clone(),
+                    })
+            })
+            .collect::<Result<BTreeMap<_, _>, _>>()?;
+        let kind = match &legend.kind {
+            Some(name) => RegistryId::from_name(name)
+                .ok_or_else(|| LevelAsciiError::UnknownKind(name.clone()))?,
+            None => RegistryId::new::<DefaultLevel>(),
+        };
+
+        let mut level_tiles = Vec::with_capacity(width * lines.len());
+        for (index, line) in lines.iter().enumerate() {
+            let len = line.chars().count();
+            if len != width {
+                return Err(LevelAsciiError::UnevenLine {
+                    line: index + 1,
+                    len,
+                    expected: width,
+                });
+            }
+            for (column, ch) in line.chars().enumerate() {
+                let tile = tiles.get(&ch).ok_or(LevelAsciiError::UnknownChar {
+                    ch,
+                    line: index + 1,
+                    column: column + 1,
+                })?;
+                level_tiles.push(tile.clone());
+            }
+        }
+
+        Ok(Self {
+            tiles: level_tiles,
+            kind,
+            size: IVec2::new(width as i32, lines.len() as i32),
+        })
+    }
+}
+
+/// Loads [`Level`] from `.txt` and `.lvl` text maps using the legend, see [`Level::from_ascii`]
+pub struct LevelAsciiLoader<R: Registry> {
+    legend: LevelLegend,
+    scope: Option<RegistryScope>,
+    _marker: PhantomData<R>,
+}
+
+impl<R: Registry> LevelAsciiLoader<R> {
+    pub fn new(legend: LevelLegend) -> Self {
+        Self {
+            legend,
+            scope: None,
+            _marker: PhantomData,
+        }
+    }
+
+    /// Looks up the names of the legend in the scope of an app, the loader runs outside of it
+    pub fn with_scope(mut self, scope: RegistryScope) -> Self {
+        self.scope = Some(scope);
+        self
+    }
+}
+
+impl<R: Registry> AssetLoader for LevelAsciiLoader<R> {
+    type Asset = Level<R>;
+    type Settings = ();
+    type Error = LevelAsciiError;
+
+    fn load<'a>(
+        &'a self,
+        reader: &'a mut Reader,
+        _settings: &'a Self::Settings,
+        _load_context: &'a mut LoadContext,
+    ) -> BoxedFuture<'a, Result<Self::Asset, Self::Error>> {
+        Box::pin(async move {
+            let mut bytes = Vec::new();
+            reader
+                .read_to_end(&mut bytes)
+                .await
+                .map_err(LevelAsciiError::Io)?;
+            let map = String::from_utf8(bytes).map_err(LevelAsciiError::Utf8)?;
+            match &self.scope {
+                Some(scope) => scope.enter(|| Level::from_ascii(&map, &self.legend)),
+                None => Level::from_ascii(&map, &self.legend),
+            }
+        })
+    }
+
+    fn extensions(&self) -> &[&str] {
+        &["txt", "lvl"]
+    }
+}
+
+/// Adds [`Level`] asset and [`LevelAsciiLoader`] with the legend
+pub struct LevelAsciiPlugin<R: Registry> {
+    pub legend: LevelLegend,
+    _marker: PhantomData<R>,
+}
+
+impl<R: Registry> LevelAsciiPlugin<R> {
+    pub fn new(legend: LevelLegend) -> Self {
+        Self {
+            legend,
+            _marker: PhantomData,
+        }
+    }
+}
+
+impl<R: Registry> Plugin for LevelAsciiPlugin<R> {
+    fn build(&self, app: &mut App) {
+        let scope = app
+            .world
+            .get_resource_or_insert_with(RegistryScope::default)
+            .clone();
+        app.init_asset::<Level<R>>().register_asset_loader(
+            LevelAsciiLoader::<R>::new(self.legend.clone()).with_scope(scope),
+        );
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use std::{path::Path, time::Duration};
+
+    use bevy::{
+        asset::io::{
+            memory::{Dir, MemoryAssetReader},
+            AssetSource, AssetSourceId,
+        },
+        core::TaskPoolPlugin,
+    };
+
+    use super::*;
+
+    new_registry!(AsciiTestRegistry, u8);
+    new_registry_items!(AsciiTestRegistry {
+        AsciiWall,
+        AsciiFloor,
+    });
+
+    fn legend() -> LevelLegend {
+        LevelLegend::new()
+            .with_tile('#', "ascii_wall")
+            .with_tile('.', "ascii_floor")
+    }
+
+    fn app() -> App {
+        let mut app = App::new();
+        app.register_name::<AsciiWall>("ascii_wall")
+            .register_name::<AsciiFloor>("ascii_floor")
+            .add_plugins(crate::LevelPlugin);
+        app
+    }
+
+    #[test]
+    fn valid_map() {
+        let app = app();
+        RegistryScope::of(&app.world).enter(|| {
+            let level = Level::<AsciiTestRegistry>::from_ascii("###\n#..\n\n", &legend()).unwrap();
+            let wall = RegistryId::new::<AsciiWall>();
+            let floor = RegistryId::new::<AsciiFloor>();
+            assert_eq!(level.size, IVec2::new(3, 2));
+            assert!(level.tiles == vec![wall, wall, wall, wall, floor, floor]);
+            assert!(level.kind.is::<DefaultLevel>());
+        });
+    }
+
+    #[test]
+    fn non_square_iter() {
+        let app = app();
+        RegistryScope::of(&app.world).enter(|| {
+            let level = Level::<AsciiTestRegistry>::from_ascii("#.\n..\n.#", &legend()).unwrap();
+            assert_eq!(level.size, IVec2::new(2, 3));
+            let walls = level
+                .iter()
+                .filter(|(_, tile)| tile.is::<AsciiWall>())
+                .map(|(pos, _)| pos)
+                .collect::<Vec<_>>();
+            assert_eq!(walls, [IVec2::new(0, 0), IVec2::new(1, 2)]);
+            for (pos, tile) in level.iter() {
+                assert!(level.get(pos) == Some(tile));
+            }
+        });
+    }
+
+    #[test]
+    fn ragged_rows() {
+        let app = app();
+        RegistryScope::of(&app.world).enter(|| {
+            assert!(matches!(
+                Level::<AsciiTestRegistry>::from_ascii("###\n#.\n###", &legend()),
+                Err(LevelAsciiError::UnevenLine {
+                    line: 2,
+                    len: 2,
+                    expected: 3,
+                })
+            ));
+        });
+    }
+
+    #[test]
+    fn unknown_char() {
+        let app = app();
+        RegistryScope::of(&app.world).enter(|| {
+            assert!(matches!(
+                Level::<AsciiTestRegistry>::from_ascii("###\n#.#\n#.x", &legend()),
+                Err(LevelAsciiError::UnknownChar {
+                    ch: 'x',
+                    line: 3,
+                    column: 3,
+                })
+            ));
+            assert!(matches!(
+                Level::<AsciiTestRegistry>::from_ascii("", &legend()),
+                Err(LevelAsciiError::Empty)
+            ));
+        });
+    }
+
+    #[test]
+    fn loader() {
+        let dir = Dir::default();
+        dir.insert_asset_text(Path::new("room.txt"), "###\n#.#\n###\n");
+        dir.insert_asset_text(Path::new("corridor.lvl"), "....\n");
+        let reader = MemoryAssetReader { root: dir };
+
+        let mut app = app();
+        app.register_asset_source(
+            AssetSourceId::Default,
+            AssetSource::build().with_reader(move || Box::new(reader.clone())),
+        )
+        .add_plugins((
+            TaskPoolPlugin::default(),
+            AssetPlugin::default(),
+            LevelAsciiPlugin::<AsciiTestRegistry>::new(legend()),
+        ));
+
+        let server = app.world.resource::<AssetServer>().clone();
+        let room = server.load::<Level<AsciiTestRegistry>>("room.txt");
+        let corridor = server.load::<Level<AsciiTestRegistry>>("corridor.lvl");
+        for _ in 0..1000 {
+            app.update();
+            let levels = app.world.resource::<Assets<Level<AsciiTestRegistry>>>();
+            if levels.contains(&room) && levels.contains(&corridor) {
+                break;
+            }
+            std::thread::sleep(Duration::from_millis(1));
+        }
+
+        let levels = app.world.resource::<Assets<Level<AsciiTestRegistry>>>();
+        let floor = RegistryId::new::<AsciiFloor>();
+        assert_eq!(levels.get(&room).unwrap().size, IVec2::new(3, 3));
+        assert!(levels.get(&room).unwrap().tiles[4] == floor);
+        assert!(levels.get(&corridor).unwrap().tiles == vec![floor; 4]);
+    }
+}
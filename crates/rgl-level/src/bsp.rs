@@ -0,0 +1,513 @@
+use std::fmt;
+
+use bevy::prelude::*;
+use fastrand::Rng;
+use rgl_registry::*;
+
+use crate::{DefaultLevel, Level, LevelKindRegistry};
+
+/// The items, that generators put into the generated levels
+pub struct LevelPalette<R: Registry> {
+    pub wall: RegistryId<R>,
+    pub floor: RegistryId<R>,
+    pub door: RegistryId<R>,
+    /// Is used outside of the rooms and the corridors
+    pub void: RegistryId<R>,
+}
+
+impl<R: Registry> Clone for LevelPalette<R> {
+    fn clone(&self) -> Self {
+        Self {
+            wall: self.wall.clone(),
+            floor: self.floor.clone(),
+            door: self.door.clone(),
+            void: self.void.clone(),
+        }
+    }
+}
+
+/// Generates rooms connected by corridors with binary space partitioning.
+///
+/// The level is split in two parts until the parts are too small or [`BspGenerator::depth`]
+/// is reached, a room is placed in every part and the rooms of the sibling parts are connected.
+/// The walls surround the rooms and the corridors, the doors are placed where the corridors
+/// enter the rooms. The same seed always generates the same level
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub struct BspGenerator {
+    pub size: IVec2,
+    /// The parts smaller than this are not split, must be at least 2 tiles larger than
+    /// [`BspGenerator::min_room_size`], so the room and its walls fit into the part
+    pub min_part_size: IVec2,
+    /// Must be at least 1 tile in both directions
+    pub min_room_size: IVec2,
+    pub depth: u32,
+    pub kind: RegistryId<LevelKindRegistry>,
+}
+
+impl BspGenerator {
+    pub fn new(size: IVec2) -> Self {
+        Self {
+            size,
+            min_part_size: IVec2::new(8, 8),
+            min_room_size: IVec2::new(4, 4),
+            depth: 6,
+            kind: RegistryId::new::<DefaultLevel>(),
+        }
+    }
+
+    pub fn with_min_part_size(mut self, min_part_size: IVec2) -> Self {
+        self.min_part_size = min_part_size;
+        self
+    }
+
+    pub fn with_min_room_size(mut self, min_room_size: IVec2) -> Self {
+        self.min_room_size = min_room_size;
+        self
+    }
+
+    pub fn with_depth(mut self, depth: u32) -> Self {
+        self.depth = depth;
+        self
+    }
+
+    pub fn with_kind(mut self, kind: RegistryId<LevelKindRegistry>) -> Self {
+        self.kind = kind;
+        self
+    }
+
+    pub fn generate<R: Registry>(
+        &self,
+        palette: &LevelPalette<R>,
+        seed: u64,
+    ) -> Result<BspDungeon<R>, BspError> {
+        if self.min_room_size.cmplt(IVec2::ONE).any() {
+            return Err(BspError::RoomTooSmall {
+                min_room_size: self.min_room_size,
+            });
+        }
+        if self.min_part_size.cmplt(self.min_room_size + 2).any() {
+            return Err(BspError::PartTooSmall {
+                min_part_size: self.min_part_size,
+                min_room_size: self.min_room_size,
+            });
+        }
+        if self.size.cmplt(self.min_part_size).any() {
+            return Err(BspError::LevelTooSmall {
+                size: self.size,
+                min_part_size: self.min_part_size,
+            });
+        }
+
+        let mut rng = Rng::with_seed(seed);
+        let mut cells = Cells::new(self.size);
+        let mut rooms = vec![];
+        self.split(
+            &mut rng,
+            &mut cells,
+            &mut rooms,
+            IRect::from_corners(IVec2::ZERO, self.size),
+            0,
+        );
+        cells.surround_with_walls();
+        cells.place_doors();
+
+        Ok(BspDungeon {
+            level: Level {
+                tiles: cells
+                    .cells
+                    .into_iter()
+                    .map(|cell| match cell {
+                        Cell::Void => palette.void.clone(),
+                        Cell::Room | Cell::Corridor => palette.floor.clone(),
+                        Cell::Wall => palette.wall.clone(),
+                        Cell::Door => palette.door.clone(),
+                    })
+                    .collect(),
+                kind: self.kind,
+                size: self.size,
+            },
+            rooms,
+        })
+    }
+
+    /// Fills the part with rooms and returns the index of one of them,
+    /// the rooms of the sibling parts are connected with a corridor
+    fn split(
+        &self,
+        rng: &mut Rng,
+        cells: &mut Cells,
+        rooms: &mut Vec<IRect>,
+        part: IRect,
+        depth: u32,
+    ) -> usize {
+        let size = part.size();
+        let can_split = size.cmpge(self.min_part_size * 2);
+        // the long parts are split across, so the rooms are not too narrow
+        let vertical = match (can_split.x, can_split.y) {
+            _ if depth >= self.depth => None,
+            (true, true) if size.x * 4 > size.y * 5 => Some(true),
+            (true, true) if size.y * 4 > size.x * 5 => Some(false),
+            (true, true) => Some(rng.bool()),
+            (true, false) => Some(true),
+            (false, true) => Some(false),
+            (false, false) => None,
+        };
+
+        let Some(vertical) = vertical else {
+            let room_size = IVec2::new(
+                rng.i32(self.min_room_size.x..=size.x - 2),
+                rng.i32(self.min_room_size.y..=size.y - 2),
+            );
+            let min = part.min
+                + IVec2::new(
+                    rng.i32(1..=size.x - 1 - room_size.x),
+                    rng.i32(1..=size.y - 1 - room_size.y),
+                );
+            let room = IRect::from_corners(min, min + room_size);
+            cells.fill(room, Cell::Room);
+            rooms.push(room);
+            return rooms.len() - 1;
+        };
+
+        let (first, second) = match vertical {
+            true => {
+                let x =
+                    rng.i32(part.min.x + self.min_part_size.x..=part.max.x - self.min_part_size.x);
+                (
+                    IRect::from_corners(part.min, IVec2::new(x, part.max.y)),
+                    IRect::from_corners(IVec2::new(x, part.min.y), part.max),
+                )
+            }
+            false => {
+                let y =
+                    rng.i32(part.min.y + self.min_part_size.y..=part.max.y - self.min_part_size.y);
+                (
+                    IRect::from_corners(part.min, IVec2::new(part.max.x, y)),
+                    IRect::from_corners(IVec2::new(part.min.x, y), part.max),
+                )
+            }
+        };
+        let first = self.split(rng, cells, rooms, first, depth + 1);
+        let second = self.split(rng, cells, rooms, second, depth + 1);
+        let from = room_cell(rng, rooms[first]);
+        let to = room_cell(rng, rooms[second]);
+        cells.connect(rng, from, to);
+        match rng.bool() {
+            true => first,
+            false => second,
+        }
+    }
+}
+
+fn room_cell(rng: &mut Rng, room: IRect) -> IVec2 {
+    IVec2::new(
+        rng.i32(room.min.x..room.max.x),
+        rng.i32(room.min.y..room.max.y),
+    )
+}
+
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum BspError {
+    /// The minimal room is not at least 1 tile large
+    RoomTooSmall { min_room_size: IVec2 },
+    /// The minimal room and its walls do not fit into the minimal part
+    PartTooSmall {
+        min_part_size: IVec2,
+        min_room_size: IVec2,
+    },
+    /// The level is smaller than the minimal part
+    LevelTooSmall { size: IVec2, min_part_size: IVec2 },
+}
+
+impl fmt::Display for BspError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            Self::RoomTooSmall { min_room_size } => write!(
+                f,
+                "Minimal room size {} must be at least 1 tile in both directions",
+                min_room_size
+            ),
+            Self::PartTooSmall {
+                min_part_size,
+                min_room_size,
+            } => write!(
+                f,
+                "Minimal part size {} can not fit the minimal room size {} with the walls",
+                min_part_size, min_room_size
+            ),
+            Self::LevelTooSmall {
+                size,
+                min_part_size,
+            } => write!(
+                f,
+                "Level size {} is smaller than the minimal part size {}",
+                size, min_part_size
+            ),
+        }
+    }
+}
+
+impl std::error::Error for BspError {}
+
+/// A level generated by [`BspGenerator`]
+pub struct BspDungeon<R: Registry> {
+    pub level: Level<R>,
+    /// The floor of the rooms, the maximum corner is not included
+    pub rooms: Vec<IRect>,
+}
+
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+enum Cell {
+    Void,
+    Room,
+    Corridor,
+    Wall,
+    Door,
+}
+
+struct Cells {
+    size: IVec2,
+    cells: Vec<Cell>,
+}
+
+impl Cells {
+    fn new(size: IVec2) -> Self {
+        Self {
+            size,
+            cells: vec![Cell::Void; (size.x * size.y) as usize],
+        }
+    }
+
+    fn get(&self, pos: IVec2) -> Cell {
+        if pos.cmplt(IVec2::ZERO).any() || pos.cmpge(self.size).any() {
+            Cell::Void
+        } else {
+            self.cells[(pos.x + pos.y * self.size.x) as usize]
+        }
+    }
+
+    fn set(&mut self, pos: IVec2, cell: Cell) {
+        self.cells[(pos.x + pos.y * self.size.x) as usize] = cell;
+    }
+
+    fn fill(&mut self, rect: IRect, cell: Cell) {
+        for y in rect.min.y..rect.max.y {
+            for x in rect.min.x..rect.max.x {
+                self.set(IVec2::new(x, y), cell);
+            }
+        }
+    }
+
+    fn is_floor(&self, pos: IVec2) -> bool {
+        matches!(self.get(pos), Cell::Room | Cell::Corridor)
+    }
+
+    /// Digs an L-shaped corridor, the cells of the rooms stay as they are
+    fn connect(&mut self, rng: &mut Rng, from: IVec2, to: IVec2) {
+        let corner = match rng.bool() {
+            true => IVec2::new(to.x, from.y),
+            false => IVec2::new(from.x, to.y),
+        };
+        for (start, end) in [(from, corner), (corner, to)] {
+            let step = (end - start).signum();
+            let mut pos = start;
+            loop {
+                if self.get(pos) == Cell::Void {
+                    self.set(pos, Cell::Corridor);
+                }
+                if pos == end {
+                    break;
+                }
+                pos += step;
+            }
+        }
+    }
+
+    fn surround_with_walls(&mut self) {
+        for y in 0..self.size.y {
+            for x in 0..self.size.x {
+                let pos = IVec2::new(x, y);
+                if self.get(pos) != Cell::Void {
+                    continue;
+                }
+                let near_floor = (-1..=1)
+                    .flat_map(|dy| (-1..=1).map(move |dx| IVec2::new(dx, dy)))
+                    .any(|offset| self.is_floor(pos + offset));
+                if near_floor {
+                    self.set(pos, Cell::Wall);
+                }
+            }
+        }
+    }
+
+    /// A door is a corridor cell next to a room, that is a passage between two walls
+    fn place_doors(&mut self) {
+        for y in 0..self.size.y {
+            for x in 0..self.size.x {
+                let pos = IVec2::new(x, y);
+                if self.get(pos) != Cell::Corridor {
+                    continue;
+                }
+                let near_room = [IVec2::X, IVec2::NEG_X, IVec2::Y, IVec2::NEG_Y]
+                    .into_iter()
+                    .any(|offset| self.get(pos + offset) == Cell::Room);
+                let passage = [IVec2::X, IVec2::Y].into_iter().any(|along| {
+                    let across = along.perp();
+                    self.is_floor(pos + along)
+                        && self.is_floor(pos - along)
+                        && self.get(pos + across) == Cell::Wall
+                        && self.get(pos - across) == Cell::Wall
+                });
+                if near_room && passage {
+                    self.set(pos, Cell::Door);
+                }
+            }
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    new_registry!(BspTestRegistry, u8);
+    new_registry_items!(BspTestRegistry {
+        BspWall,
+        BspFloor,
+        BspDoor,
+        BspVoid,
+    });
+
+    fn palette() -> LevelPalette<BspTestRegistry> {
+        LevelPalette {
+            wall: RegistryId::new::<BspWall>(),
+            floor: RegistryId::new::<BspFloor>(),
+            door: RegistryId::new::<BspDoor>(),
+            void: RegistryId::new::<BspVoid>(),
+        }
+    }
+
+    fn generate(seed: u64) -> BspDungeon<BspTestRegistry> {
+        BspGenerator::new(IVec2::new(64, 48))
+            .generate(&palette(), seed)
+            .unwrap()
+    }
+
+    fn tile(level: &Level<BspTestRegistry>, pos: IVec2) -> Option<&RegistryId<BspTestRegistry>> {
+        match pos.cmplt(IVec2::ZERO).any() || pos.cmpge(level.size).any() {
+            true => None,
+            false => level.tiles.get((pos.x + pos.y * level.size.x) as usize),
+        }
+    }
+
+    #[test]
+    fn same_seed_same_level() {
+        assert!(generate(7).level.tiles == generate(7).level.tiles);
+        assert_eq!(generate(7).rooms, generate(7).rooms);
+        assert!(generate(7).level.tiles != generate(8).level.tiles);
+    }
+
+    #[test]
+    fn rooms_are_floor() {
+        let palette = palette();
+        for seed in 0..16 {
+            let dungeon = generate(seed);
+            assert!(dungeon.rooms.len() > 1);
+            for room in &dungeon.rooms {
+                assert!(room.min.cmpge(IVec2::ONE).all());
+                assert!(room.max.cmple(dungeon.level.size - 1).all());
+                for y in room.min.y..room.max.y {
+                    for x in room.min.x..room.max.x {
+                        assert!(tile(&dungeon.level, IVec2::new(x, y)) == Some(&palette.floor));
+                    }
+                }
+            }
+        }
+    }
+
+    #[test]
+    fn rooms_are_reachable() {
+        let palette = palette();
+        for seed in 0..16 {
+            let dungeon = generate(seed);
+            let walkable = |pos| {
+                tile(&dungeon.level, pos)
+                    .is_some_and(|tile| *tile == palette.floor || *tile == palette.door)
+            };
+            let mut reached = vec![false; dungeon.level.tiles.len()];
+            let mut stack = vec![dungeon.rooms[0].min];
+            while let Some(pos) = stack.pop() {
+                let index = (pos.x + pos.y * dungeon.level.size.x) as usize;
+                if !walkable(pos) || reached[index] {
+                    continue;
+                }
+                reached[index] = true;
+                for offset in [IVec2::X, IVec2::NEG_X, IVec2::Y, IVec2::NEG_Y] {
+                    stack.push(pos + offset);
+                }
+            }
+            for room in &dungeon.rooms {
+                assert!(reached[(room.min.x + room.min.y * dungeon.level.size.x) as usize]);
+            }
+        }
+    }
+
+    #[test]
+    fn doors_are_between_walls() {
+        let palette = palette();
+        let mut doors = 0;
+        for seed in 0..16 {
+            let dungeon = generate(seed);
+            let level = &dungeon.level;
+            for y in 0..level.size.y {
+                for x in 0..level.size.x {
+                    let pos = IVec2::new(x, y);
+                    if tile(level, pos) != Some(&palette.door) {
+                        continue;
+                    }
+                    doors += 1;
+                    let is_wall = |pos| tile(level, pos) == Some(&palette.wall);
+                    assert!(
+                        (is_wall(pos + IVec2::X) && is_wall(pos - IVec2::X))
+                            || (is_wall(pos + IVec2::Y) && is_wall(pos - IVec2::Y))
+                    );
+                }
+            }
+        }
+        assert!(doors > 0);
+    }
+
+    #[test]
+    fn too_small() {
+        assert_eq!(
+            BspGenerator::new(IVec2::new(4, 4))
+                .generate(&palette(), 0)
+                .err(),
+            Some(BspError::LevelTooSmall {
+                size: IVec2::new(4, 4),
+                min_part_size: IVec2::new(8, 8),
+            })
+        );
+        assert!(matches!(
+            BspGenerator::new(IVec2::new(64, 48))
+                .with_min_part_size(IVec2::new(5, 5))
+                .generate(&palette(), 0),
+            Err(BspError::PartTooSmall { .. })
+        ));
+        assert_eq!(
+            BspGenerator::new(IVec2::new(64, 48))
+                .with_min_room_size(IVec2::new(4, 0))
+                .generate(&palette(), 0)
+                .err(),
+            Some(BspError::RoomTooSmall {
+                min_room_size: IVec2::new(4, 0),
+            })
+        );
+        assert!(matches!(
+            BspGenerator::new(IVec2::new(64, 48))
+                .with_min_room_size(IVec2::splat(-3))
+                .generate(&palette(), 0),
+            Err(BspError::RoomTooSmall { .. })
+        ));
+    }
+}
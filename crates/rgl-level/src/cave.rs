@@ -0,0 +1,304 @@
+use std::fmt;
+
+use bevy::prelude::*;
+use fastrand::Rng;
+use rgl_registry::*;
+
+use crate::{DefaultLevel, Level, LevelKindRegistry};
+
+/// Generates organic caves with cellular automata.
+///
+/// The level is filled with random walls, smoothed [`CaveGenerator::iterations`] times
+/// and then only the largest floor region is kept, so every floor tile can be reached
+/// from every other one. The border of the level is always a wall.
+/// The same seed always generates the same level
+#[derive(Clone, Debug, PartialEq)]
+pub struct CaveGenerator {
+    pub size: IVec2,
+    /// Chance of a tile to be a wall before the smoothing, from 0 to 1
+    pub fill: f32,
+    pub iterations: u32,
+    /// A floor tile becomes a wall if at least this many of its 8 neighbours are walls
+    pub birth: u32,
+    /// A wall stays a wall if at least this many of its 8 neighbours are walls
+    pub survival: u32,
+    pub kind: RegistryId<LevelKindRegistry>,
+}
+
+impl CaveGenerator {
+    pub fn new(size: IVec2) -> Self {
+        Self {
+            size,
+            fill: 0.45,
+            iterations: 5,
+            birth: 5,
+            survival: 4,
+            kind: RegistryId::new::<DefaultLevel>(),
+        }
+    }
+
+    pub fn with_fill(mut self, fill: f32) -> Self {
+        self.fill = fill;
+        self
+    }
+
+    pub fn with_iterations(mut self, iterations: u32) -> Self {
+        self.iterations = iterations;
+        self
+    }
+
+    pub fn with_thresholds(mut self, birth: u32, survival: u32) -> Self {
+        self.birth = birth;
+        self.survival = survival;
+        self
+    }
+
+    pub fn with_kind(mut self, kind: RegistryId<LevelKindRegistry>) -> Self {
+        self.kind = kind;
+        self
+    }
+
+    /// If no floor is left after the smoothing, the center of the level becomes the only floor tile
+    pub fn generate<R: Registry>(
+        &self,
+        wall: RegistryId<R>,
+        floor: RegistryId<R>,
+        seed: u64,
+    ) -> Result<Level<R>, CaveError> {
+        if self.size.cmplt(IVec2::splat(3)).any() {
+            return Err(CaveError::TooSmall(self.size));
+        }
+
+        let mut rng = Rng::with_seed(seed);
+        let mut walls = Walls {
+            size: self.size,
+            walls: vec![true; (self.size.x * self.size.y) as usize],
+        };
+        for pos in walls.inner() {
+            walls.set(pos, rng.f32() < self.fill);
+        }
+        for _ in 0..self.iterations {
+            walls = walls.smooth(self.birth, self.survival);
+        }
+        walls.keep_largest_region();
+
+        Ok(Level {
+            tiles: walls
+                .walls
+                .into_iter()
+                .map(|is_wall| match is_wall {
+                    true => wall.clone(),
+                    false => floor.clone(),
+                })
+                .collect(),
+            kind: self.kind,
+            size: self.size,
+        })
+    }
+}
+
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum CaveError {
+    /// The level can not fit a floor tile inside of the walls
+    TooSmall(IVec2),
+}
+
+impl fmt::Display for CaveError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            Self::TooSmall(size) => write!(
+                f,
+                "Level size {} can not fit a floor tile inside of the walls",
+                size
+            ),
+        }
+    }
+}
+
+impl std::error::Error for CaveError {}
+
+struct Walls {
+    size: IVec2,
+    walls: Vec<bool>,
+}
+
+impl Walls {
+    /// Positions of the tiles, that are not on the border
+    fn inner(&self) -> impl Iterator<Item = IVec2> {
+        let size = self.size;
+        (1..size.y - 1).flat_map(move |y| (1..size.x - 1).map(move |x| IVec2::new(x, y)))
+    }
+
+    fn index(&self, pos: IVec2) -> usize {
+        (pos.x + pos.y * self.size.x) as usize
+    }
+
+    /// The tiles outside of the level are walls
+    fn get(&self, pos: IVec2) -> bool {
+        pos.cmplt(IVec2::ZERO).any() || pos.cmpge(self.size).any() || self.walls[self.index(pos)]
+    }
+
+    fn set(&mut self, pos: IVec2, wall: bool) {
+        let index = self.index(pos);
+        self.walls[index] = wall;
+    }
+
+    fn smooth(&self, birth: u32, survival: u32) -> Self {
+        let mut smoothed = Self {
+            size: self.size,
+            walls: self.walls.clone(),
+        };
+        for pos in self.inner() {
+            let neighbours = (-1..=1)
+                .flat_map(|dy| (-1..=1).map(move |dx| IVec2::new(dx, dy)))
+                .filter(|&offset| offset != IVec2::ZERO && self.get(pos + offset))
+                .count() as u32;
+            let threshold = match self.get(pos) {
+                true => survival,
+                false => birth,
+            };
+            smoothed.set(pos, neighbours >= threshold);
+        }
+        smoothed
+    }
+
+    /// Fills every floor region except the largest one with walls,
+    /// the regions are connected by the sides of the tiles
+    fn keep_largest_region(&mut self) {
+        let mut regions = vec![usize::MAX; self.walls.len()];
+        let mut sizes = vec![];
+        let mut stack = vec![];
+        for start in self.inner() {
+            if self.get(start) || regions[self.index(start)] != usize::MAX {
+                continue;
+            }
+            let region = sizes.len();
+            let mut size = 0;
+            regions[self.index(start)] = region;
+            stack.push(start);
+            while let Some(pos) = stack.pop() {
+                size += 1;
+                for offset in [IVec2::X, IVec2::NEG_X, IVec2::Y, IVec2::NEG_Y] {
+                    let next = pos + offset;
+                    if !self.get(next) && regions[self.index(next)] == usize::MAX {
+                        regions[self.index(next)] = region;
+                        stack.push(next);
+                    }
+                }
+            }
+            sizes.push(size);
+        }
+
+        // max_by_key returns the last maximum, so the first of the largest regions is kept
+        let largest = sizes
+            .iter()
+            .enumerate()
+            .rev()
+            .max_by_key(|&(_, size)| size)
+            .map(|(region, _)| region);
+        match largest {
+            Some(largest) => {
+                for (wall, region) in self.walls.iter_mut().zip(regions) {
+                    *wall = region != largest;
+                }
+            }
+            None => self.set(self.size / 2, false),
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    new_registry!(CaveTestRegistry, u8);
+    new_registry_items!(CaveTestRegistry {
+        CaveWall,
+        CaveFloor
+    });
+
+    fn generate(generator: &CaveGenerator, seed: u64) -> Level<CaveTestRegistry> {
+        generator
+            .generate(
+                RegistryId::new::<CaveWall>(),
+                RegistryId::new::<CaveFloor>(),
+                seed,
+            )
+            .unwrap()
+    }
+
+    fn floor(level: &Level<CaveTestRegistry>) -> Vec<bool> {
+        let floor = RegistryId::new::<CaveFloor>();
+        level.tiles.iter().map(|tile| *tile == floor).collect()
+    }
+
+    #[test]
+    fn same_seed_same_level() {
+        let generator = CaveGenerator::new(IVec2::new(48, 32));
+        assert!(generate(&generator, 3).tiles == generate(&generator, 3).tiles);
+        assert!(generate(&generator, 3).tiles != generate(&generator, 4).tiles);
+    }
+
+    #[test]
+    fn floor_is_connected() {
+        let generator = CaveGenerator::new(IVec2::new(48, 32));
+        for seed in 0..16 {
+            let level = generate(&generator, seed);
+            let floor = floor(&level);
+            let index = |pos: IVec2| (pos.x + pos.y * level.size.x) as usize;
+            let start = floor.iter().position(|&floor| floor).unwrap() as i32;
+            let mut reached = vec![false; floor.len()];
+            let mut stack = vec![IVec2::new(start % level.size.x, start / level.size.x)];
+            while let Some(pos) = stack.pop() {
+                if !floor[index(pos)] || reached[index(pos)] {
+                    continue;
+                }
+                reached[index(pos)] = true;
+                for offset in [IVec2::X, IVec2::NEG_X, IVec2::Y, IVec2::NEG_Y] {
+                    stack.push(pos + offset);
+                }
+            }
+            assert_eq!(floor, reached);
+        }
+    }
+
+    #[test]
+    fn border_is_wall() {
+        let generator = CaveGenerator::new(IVec2::new(48, 32)).with_fill(0.0);
+        for seed in 0..4 {
+            let level = generate(&generator, seed);
+            let floor = floor(&level);
+            for y in 0..level.size.y {
+                for x in 0..level.size.x {
+                    let border = x == 0 || y == 0 || x == level.size.x - 1 || y == level.size.y - 1;
+                    if border {
+                        assert!(!floor[(x + y * level.size.x) as usize]);
+                    }
+                }
+            }
+        }
+    }
+
+    #[test]
+    fn full_fill_keeps_one_floor() {
+        let generator = CaveGenerator::new(IVec2::new(9, 7)).with_fill(1.0);
+        let level = generate(&generator, 0);
+        let floor = floor(&level);
+        assert_eq!(floor.iter().filter(|&&floor| floor).count(), 1);
+        assert!(floor[(4 + 3 * 9) as usize]);
+    }
+
+    #[test]
+    fn too_small() {
+        assert_eq!(
+            CaveGenerator::new(IVec2::new(2, 8))
+                .generate(
+                    RegistryId::<CaveTestRegistry>::new::<CaveWall>(),
+                    RegistryId::new::<CaveFloor>(),
+                    0,
+                )
+                .err(),
+            Some(CaveError::TooSmall(IVec2::new(2, 8)))
+        );
+    }
+}
@@ -0,0 +1,866 @@
+use std::{
+    collections::BTreeMap,
+    fmt,
+    path::{Path, PathBuf},
+};
+
+use bevy::{
+    asset::{
+        io::{file::FileAssetReader, Reader},
+        AssetLoader, AsyncReadExt, LoadContext, ReadAssetBytesError,
+    },
+    prelude::*,
+    reflect::TypePath,
+    utils::{BoxedFuture, HashMap},
+};
+use rgl_registry::*;
+use serde::Deserialize;
+
+use crate::{Level, LevelBundle};
+
+/// A project exported from LDtk as `.ldtk` JSON, the levels can also be saved in `.ldtkl` files.
+///
+/// Every level becomes a [`Level`], where the values of the IntGrid layer are mapped
+/// to the registry items by their identifiers, and the entity instances become
+/// [`LevelSpawnMarker`]s, see [`LdtkImport`]
+#[derive(Asset, TypePath, Clone, Debug, Default, PartialEq)]
+pub struct LdtkProject {
+    pub layers: Vec<LdtkLayerDef>,
+    pub levels: Vec<LdtkLevel>,
+}
+
+#[derive(Clone, Debug, Default, PartialEq, Eq)]
+pub struct LdtkLayerDef {
+    pub identifier: String,
+    /// Identifiers of the IntGrid values, the values without identifiers are not included
+    pub int_grid_values: BTreeMap<i32, String>,
+}
+
+#[derive(Clone, Debug, Default, PartialEq)]
+pub struct LdtkLevel {
+    pub identifier: String,
+    /// Path of the `.ldtkl` file of the level, the layers are not loaded yet if it is set
+    pub external: Option<PathBuf>,
+    pub fields: BTreeMap<String, String>,
+    pub layers: Vec<LdtkLayer>,
+}
+
+/// A layer instance of the level, the cells are stored row by row starting from the top one
+#[derive(Clone, Debug, Default, PartialEq)]
+pub struct LdtkLayer {
+    pub identifier: String,
+    pub width: u32,
+    pub height: u32,
+    pub grid_size: u32,
+    /// IntGrid values of the cells, is empty if the layer is not an IntGrid layer, 0 means no value
+    pub int_grid: Vec<i32>,
+    pub entities: Vec<LdtkEntity>,
+}
+
+#[derive(Clone, Debug, Default, PartialEq)]
+pub struct LdtkEntity {
+    pub identifier: String,
+    /// Cell of the entity in the grid of its layer
+    pub pos: IVec2,
+    pub fields: BTreeMap<String, String>,
+}
+
+/// A place, where the game code should spawn something, e.g. an actor.
+/// Is spawned as a child of the level entity by [`LdtkProject::spawn`]
+#[derive(Component, Clone, Debug, Default, PartialEq, Reflect)]
+#[reflect(Component)]
+pub struct LevelSpawnMarker {
+    pub identifier: String,
+    pub pos: IVec2,
+    pub fields: HashMap<String, String>,
+}
+
+/// How the levels of [`LdtkProject`] are turned into [`Level`]s
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub struct LdtkImport {
+    /// Identifier of the IntGrid layer with the tiles, the first IntGrid layer is used if it is not set
+    pub int_grid: Option<String>,
+    /// Name of the item used for the cells without a value
+    pub empty: Option<String>,
+    /// The field of the level with the name of the level kind, `kind` by default.
+    /// The identifier of the level is used if the level has no such field
+    pub kind_field: String,
+}
+
+impl Default for LdtkImport {
+    fn default() -> Self {
+        Self {
+            int_grid: None,
+            empty: None,
+            kind_field: "kind".to_owned(),
+        }
+    }
+}
+
+#[derive(Debug)]
+pub enum LdtkError {
+    Io(PathBuf, std::io::Error),
+    /// The asset server couldn't read the `.ldtkl` file of the level
+    Read(PathBuf, ReadAssetBytesError),
+    Json(serde_json::Error),
+    /// The level is saved in a separate file, that is not loaded,
+    /// see [`LdtkProject::load`] and [`LdtkProjectLoader`]
+    External(String),
+    /// The level has no IntGrid layer with the identifier of [`LdtkImport::int_grid`]
+    MissingLayer {
+        level: String,
+        layer: Option<String>,
+    },
+    /// The value has no identifier or the cell is empty and [`LdtkImport::empty`] is not set
+    UnmappedValue {
+        level: String,
+        x: u32,
+        y: u32,
+        value: i32,
+    },
+    /// The IntGrid layer has not as many values as cells or is too large
+    InvalidSize {
+        level: String,
+        layer: String,
+        width: u32,
+        height: u32,
+        values: usize,
+    },
+    /// The item is not registered in the [`RegistryNameCell`]
+    UnknownItem(String),
+    /// The level kind is not registered in the [`RegistryNameCell`]
+    UnknownKind(String),
+}
+
+impl fmt::Display for LdtkError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            Self::Io(path, err) => write!(f, "Couldn't read {}: {}", path.display(), err),
+            Self::Read(path, err) => write!(f, "Couldn't read {}: {}", path.display(), err),
+            Self::Json(err) => write!(f, "Couldn't parse LDtk JSON: {}", err),
+            Self::External(level) => write!(f, "Level {} is saved in a separate file", level),
+            Self::MissingLayer {
+                level,
+                layer: Some(layer),
+            } => write!(f, "Level {} has no IntGrid layer {}", level, layer),
+            Self::MissingLayer { level, layer: None } => {
+                write!(f, "Level {} has no IntGrid layer", level)
+            }
+            Self::UnmappedValue { level, x, y, value } => write!(
+                f,
+                "Value {} at {}, {} of level {} has no registry item",
+                value, x, y, level
+            ),
+            Self::InvalidSize {
+                level,
+                layer,
+                width,
+                height,
+                values,
+            } => write!(
+                f,
+                "IntGrid layer {} of level {} has {} values, but is {}x{} cells large",
+                layer, level, values, width, height
+            ),
+            Self::UnknownItem(name) => write!(f, "Registry item {} is not registered", name),
+            Self::UnknownKind(name) => write!(f, "Level kind {} is not registered", name),
+        }
+    }
+}
+
+impl std::error::Error for LdtkError {}
+
+impl LdtkProject {
+    pub fn from_json(s: &str) -> Result<Self, LdtkError> {
+        Self::from_slice(s.as_bytes())
+    }
+
+    pub fn from_slice(bytes: &[u8]) -> Result<Self, LdtkError> {
+        let project: JsonProject = serde_json::from_slice(bytes).map_err(LdtkError::Json)?;
+        Ok(Self {
+            layers: project
+                .defs
+                .layers
+                .into_iter()
+                .map(LdtkLayerDef::from)
+                .collect(),
+            levels: project
+                .levels
+                .into_iter()
+                .chain(project.worlds.into_iter().flat_map(|world| world.levels))
+                .map(LdtkLevel::from)
+                .collect(),
+        })
+    }
+
+    /// Reads the project from the assets directory, the path is relative to it.
+    /// The levels saved in separate files are loaded too, their paths are relative to the project.
+    /// Reads the files directly, use [`LdtkProjectLoader`] to load the project with the asset server
+    pub fn load(path: impl AsRef<Path>) -> Result<Self, LdtkError> {
+        let path = path.as_ref();
+        let mut project = Self::from_json(&read_asset(path)?)?;
+        let dir = path.parent().unwrap_or(Path::new(""));
+        for level in project.levels.iter_mut() {
+            if let Some(external) = level.external.take() {
+                *level = LdtkLevel::from_json(&read_asset(&dir.join(external))?)?;
+            }
+        }
+        Ok(project)
+    }
+
+    pub fn level_by_identifier(&self, identifier: &str) -> Option<&LdtkLevel> {
+        self.levels
+            .iter()
+            .find(|level| level.identifier == identifier)
+    }
+
+    /// Creates a level of the IntGrid layer of the LDtk level
+    pub fn level<R: Registry>(
+        &self,
+        level: &LdtkLevel,
+        import: &LdtkImport,
+    ) -> Result<Level<R>, LdtkError> {
+        if level.external.is_some() {
+            return Err(LdtkError::External(level.identifier.clone()));
+        }
+        let layer = level
+            .layers
+            .iter()
+            .filter(|layer| !layer.int_grid.is_empty())
+            .find(|layer| {
+                import
+                    .int_grid
+                    .as_ref()
+                    .is_none_or(|identifier| layer.identifier.eq(identifier))
+            })
+            .ok_or_else(|| LdtkError::MissingLayer {
+                level: level.identifier.clone(),
+                layer: import.int_grid.clone(),
+            })?;
+        let size = layer
+            .width
+            .checked_mul(layer.height)
+            .filter(|_| i32::try_from(layer.width).is_ok() && i32::try_from(layer.height).is_ok());
+        if size.map(|size| size as usize) != Some(layer.int_grid.len()) {
+            return Err(LdtkError::InvalidSize {
+                level: level.identifier.clone(),
+                layer: layer.identifier.clone(),
+                width: layer.width,
+                height: layer.height,
+                values: layer.int_grid.len(),
+            });
+        }
+        let values = self
+            .layers
+            .iter()
+            .find(|def| def.identifier == layer.identifier)
+            .map(|def| &def.int_grid_values);
+        let empty = import
+            .empty
+            .as_ref()
+            .map(|name| item_by_name::<R>(name))
+            .transpose()?;
+        let kind_name = level
+            .fields
+            .get(&import.kind_field)
+            .unwrap_or(&level.identifier);
+        let kind = RegistryId::from_name(kind_name)
+            .ok_or_else(|| LdtkError::UnknownKind(kind_name.clone()))?;
+        let tiles = layer
+            .int_grid
+            .iter()
+            .enumerate()
+            .map(|(index, &value)| {
+                let unmapped = || LdtkError::UnmappedValue {
+                    level: level.identifier.clone(),
+                    x: index as u32 % layer.width,
+                    y: index as u32 / layer.width,
+                    value,
+                };
+                if value == 0 {
+                    return empty.clone().ok_or_else(unmapped);
+                }
+                let name = values
+                    .and_then(|values| values.get(&value))
+                    .ok_or_else(unmapped)?;
+                item_by_name(name)
+            })
+            .collect::<Result<Vec<_>, _>>()?;
+        Ok(Level {
+            tiles,
+            kind,
+            size: IVec2::new(layer.width as i32, layer.height as i32),
+        })
+    }
+
+    /// Creates a level of every LDtk level with the identifiers of the levels
+    pub fn levels<R: Registry>(
+        &self,
+        import: &LdtkImport,
+    ) -> Result<Vec<(String, Level<R>)>, LdtkError> {
+        self.levels
+            .iter()
+            .map(|level| Ok((level.identifier.clone(), self.level(level, import)?)))
+            .collect()
+    }
+
+    /// Spawns the level with its [`LevelSpawnMarker`]s as children
+    pub fn spawn<R: Registry>(
+        &self,
+        commands: &mut Commands,
+        level: &LdtkLevel,
+        import: &LdtkImport,
+    ) -> Result<Entity, LdtkError> {
+        let level_entity = commands
+            .spawn(LevelBundle::from_level(self.level::<R>(level, import)?))
+            .id();
+        for marker in level.spawn_markers() {
+            commands.spawn(marker).set_parent(level_entity);
+        }
+        Ok(level_entity)
+    }
+}
+
+impl LdtkLevel {
+    /// Parses a level saved in a separate `.ldtkl` file
+    pub fn from_json(s: &str) -> Result<Self, LdtkError> {
+        Self::from_slice(s.as_bytes())
+    }
+
+    pub fn from_slice(bytes: &[u8]) -> Result<Self, LdtkError> {
+        serde_json::from_slice::<JsonLevel>(bytes)
+            .map(Self::from)
+            .map_err(LdtkError::Json)
+    }
+
+    /// Returns the markers of the entity instances of every layer
+    pub fn spawn_markers(&self) -> impl Iterator<Item = LevelSpawnMarker> + '_ {
+        self.layers
+            .iter()
+            .flat_map(|layer| layer.entities.iter())
+            .map(|entity| LevelSpawnMarker {
+                identifier: entity.identifier.clone(),
+                pos: entity.pos,
+                fields: entity
+                    .fields
+                    .iter()
+                    .map(|(name, value)| (name.clone(), value.clone()))
+                    .collect(),
+            })
+    }
+}
+
+/// Loads `.ldtk` files as [`LdtkProject`]s with the levels saved in separate files
+#[derive(Default)]
+pub struct LdtkProjectLoader;
+
+impl AssetLoader for LdtkProjectLoader {
+    type Asset = LdtkProject;
+    type Settings = ();
+    type Error = LdtkError;
+
+    fn load<'a>(
+        &'a self,
+        reader: &'a mut Reader,
+        _settings: &'a Self::Settings,
+        load_context: &'a mut LoadContext,
+    ) -> BoxedFuture<'a, Result<Self::Asset, Self::Error>> {
+        Box::pin(async move {
+            let mut bytes = Vec::new();
+            reader
+                .read_to_end(&mut bytes)
+                .await
+                .map_err(|err| LdtkError::Io(load_context.path().to_owned(), err))?;
+            let mut project = LdtkProject::from_slice(&bytes)?;
+            let dir = load_context
+                .path()
+                .parent()
+                .unwrap_or(Path::new(""))
+                .to_owned();
+            for level in project.levels.iter_mut() {
+                if let Some(external) = level.external.take() {
+                    let path = dir.join(external);
+                    let bytes = load_context
+                        .read_asset_bytes(path.clone())
+                        .await
+                        .map_err(|err| LdtkError::Read(path, err))?;
+                    *level = LdtkLevel::from_slice(&bytes)?;
+                }
+            }
+            Ok(project)
+        })
+    }
+
+    fn extensions(&self) -> &[&str] {
+        &["ldtk"]
+    }
+}
+
+/// Adds [`LdtkProject`] asset and [`LdtkProjectLoader`]
+pub struct LdtkPlugin;
+
+impl Plugin for LdtkPlugin {
+    fn build(&self, app: &mut App) {
+        app.init_asset::<LdtkProject>()
+            .register_asset_loader(LdtkProjectLoader);
+    }
+}
+
+fn item_by_name<R: Registry>(name: &str) -> Result<RegistryId<R>, LdtkError> {
+    RegistryId::from_name(name).ok_or_else(|| LdtkError::UnknownItem(name.to_owned()))
+}
+
+fn read_asset(path: &Path) -> Result<String, LdtkError> {
+    let full_path = FileAssetReader::get_base_path().join("assets").join(path);
+    std::fs::read_to_string(&full_path).map_err(|err| LdtkError::Io(full_path, err))
+}
+
+#[derive(Deserialize)]
+struct JsonProject {
+    defs: JsonDefs,
+    #[serde(default)]
+    levels: Vec<JsonLevel>,
+    #[serde(default)]
+    worlds: Vec<JsonWorld>,
+}
+
+#[derive(Deserialize)]
+struct JsonWorld {
+    #[serde(default)]
+    levels: Vec<JsonLevel>,
+}
+
+#[derive(Deserialize)]
+struct JsonDefs {
+    #[serde(default)]
+    layers: Vec<JsonLayerDef>,
+}
+
+#[derive(Deserialize)]
+#[serde(rename_all = "camelCase")]
+struct JsonLayerDef {
+    identifier: String,
+    #[serde(default)]
+    int_grid_values: Vec<JsonIntGridValue>,
+}
+
+#[derive(Deserialize)]
+struct JsonIntGridValue {
+    value: i32,
+    identifier: Option<String>,
+}
+
+#[derive(Deserialize)]
+#[serde(rename_all = "camelCase")]
+struct JsonLevel {
+    identifier: String,
+    external_rel_path: Option<PathBuf>,
+    #[serde(default)]
+    field_instances: Vec<JsonField>,
+    layer_instances: Option<Vec<JsonLayer>>,
+}
+
+#[derive(Deserialize)]
+#[serde(rename_all = "camelCase")]
+struct JsonLayer {
+    #[serde(rename = "__identifier")]
+    identifier: String,
+    #[serde(rename = "__cWid")]
+    width: u32,
+    #[serde(rename = "__cHei")]
+    height: u32,
+    #[serde(rename = "__gridSize")]
+    grid_size: u32,
+    #[serde(default)]
+    int_grid_csv: Vec<i32>,
+    #[serde(default)]
+    entity_instances: Vec<JsonEntity>,
+}
+
+#[derive(Deserialize)]
+#[serde(rename_all = "camelCase")]
+struct JsonEntity {
+    #[serde(rename = "__identifier")]
+    identifier: String,
+    #[serde(rename = "__grid")]
+    grid: [i32; 2],
+    #[serde(default)]
+    field_instances: Vec<JsonField>,
+}
+
+#[derive(Deserialize)]
+struct JsonField {
+    #[serde(rename = "__identifier")]
+    identifier: String,
+    #[serde(rename = "__value")]
+    value: serde_json::Value,
+}
+
+fn fields(fields: Vec<JsonField>) -> BTreeMap<String, String> {
+    fields
+        .into_iter()
+        .filter(|field| !field.value.is_null())
+        .map(|field| {
+            let value = match field.value {
+                serde_json::Value::String(value) => value,
+                value => value.to_string(),
+            };
+            (field.identifier, value)
+        })
+        .collect()
+}
+
+impl From<JsonLayerDef> for LdtkLayerDef {
+    fn from(value: JsonLayerDef) -> Self {
+        Self {
+            identifier: value.identifier,
+            int_grid_values: value
+                .int_grid_values
+                .into_iter()
+                .filter_map(|value| Some((value.value, value.identifier?)))
+                .collect(),
+        }
+    }
+}
+
+impl From<JsonLevel> for LdtkLevel {
+    fn from(value: JsonLevel) -> Self {
+        Self {
+            identifier: value.identifier,
+            // the layers of the level are null if it is saved in a separate file
+            external: value
+                .external_rel_path
+                .filter(|_| value.layer_instances.is_none()),
+            fields: fields(value.field_instances),
+            layers: value
+                .layer_instances
+                .unwrap_or_default()
+                .into_iter()
+                .map(LdtkLayer::from)
+                .collect(),
+        }
+    }
+}
+
+impl From<JsonLayer> for LdtkLayer {
+    fn from(value: JsonLayer) -> Self {
+        Self {
+            identifier: value.identifier,
+            width: value.width,
+            height: value.height,
+            grid_size: value.grid_size,
+            int_grid: value.int_grid_csv,
+            entities: value
+                .entity_instances
+                .into_iter()
+                .map(|entity| LdtkEntity {
+                    identifier: entity.identifier,
+                    pos: IVec2::new(entity.grid[0], entity.grid[1]),
+                    fields: fields(entity.field_instances),
+                })
+                .collect(),
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use std::time::Duration;
+
+    use bevy::{
+        asset::io::{
+            memory::{Dir, MemoryAssetReader},
+            AssetSource, AssetSourceId,
+        },
+        core::TaskPoolPlugin,
+    };
+
+    use super::*;
+    use crate::LevelKindRegistry;
+
+    new_registry!(LdtkTestRegistry, u8);
+    new_registry_items!(LdtkTestRegistry {
+        LdtkWall,
+        LdtkFloor,
+    });
+    new_registry_items!(LevelKindRegistry { LdtkCave });
+
+    const PROJECT: &str = r#"{
+        "defs": {
+            "layers": [
+                {
+                    "identifier": "Tiles",
+                    "intGridValues": [
+                        { "value": 1, "identifier": "ldtk_wall" },
+                        { "value": 2, "identifier": "ldtk_floor" },
+                        { "value": 3, "identifier": null }
+                    ]
+                },
+                { "identifier": "Entities" }
+            ]
+        },
+        "levels": [
+            {
+                "identifier": "ldtk_cave",
+                "layerInstances": [
+                    {
+                        "__identifier": "Entities",
+                        "__cWid": 3,
+                        "__cHei": 2,
+                        "__gridSize": 16,
+                        "entityInstances": [
+                            {
+                                "__identifier": "Player",
+                                "__grid": [1, 0],
+                                "fieldInstances": [
+                                    { "__identifier": "hp", "__value": 10 },
+                                    { "__identifier": "name", "__value": "hero" },
+                                    { "__identifier": "item", "__value": null }
+                                ]
+                            }
+                        ]
+                    },
+                    {
+                        "__identifier": "Tiles",
+                        "__cWid": 3,
+                        "__cHei": 2,
+                        "__gridSize": 16,
+                        "intGridCsv": [1, 1, 1, 2, 0, 2]
+                    }
+                ]
+            },
+            {
+                "identifier": "Second",
+                "fieldInstances": [{ "__identifier": "kind", "__value": "ldtk_cave" }],
+                "layerInstances": [
+                    {
+                        "__identifier": "Tiles",
+                        "__cWid": 2,
+                        "__cHei": 1,
+                        "__gridSize": 16,
+                        "intGridCsv": [1, 3]
+                    }
+                ]
+            },
+            {
+                "identifier": "NoTiles",
+                "layerInstances": [
+                    {
+                        "__identifier": "Entities",
+                        "__cWid": 2,
+                        "__cHei": 1,
+                        "__gridSize": 16
+                    }
+                ]
+            },
+            {
+                "identifier": "Outside",
+                "externalRelPath": "project/Outside.ldtkl",
+                "layerInstances": null
+            }
+        ]
+    }"#;
+
+    const OUTSIDE: &str = r#"{
+        "identifier": "Outside",
+        "fieldInstances": [{ "__identifier": "kind", "__value": "ldtk_cave" }],
+        "layerInstances": [
+            {
+                "__identifier": "Tiles",
+                "__cWid": 1,
+                "__cHei": 1,
+                "__gridSize": 16,
+                "intGridCsv": [2]
+            }
+        ]
+    }"#;
+
+    fn app() -> App {
+        let mut app = App::new();
+        app.register_name::<LdtkWall>("ldtk_wall")
+            .register_name::<LdtkFloor>("ldtk_floor")
+            .register_name::<LdtkCave>("ldtk_cave")
+            .add_plugins(crate::LevelPlugin);
+        app
+    }
+
+    fn project() -> LdtkProject {
+        LdtkProject::from_json(PROJECT).unwrap()
+    }
+
+    #[test]
+    fn kind_by_identifier() {
+        let app = app();
+        RegistryScope::of(&app.world).enter(|| {
+            let project = project();
+            let import = LdtkImport {
+                empty: Some("ldtk_floor".to_owned()),
+                ..default()
+            };
+            let level = project
+                .level::<LdtkTestRegistry>(&project.levels[0], &import)
+                .unwrap();
+            assert!(level.kind.is::<LdtkCave>());
+        });
+    }
+
+    #[test]
+    fn int_grid_values() {
+        let app = app();
+        RegistryScope::of(&app.world).enter(|| {
+            let project = project();
+            let wall = RegistryId::new::<LdtkWall>();
+            let floor = RegistryId::new::<LdtkFloor>();
+            let import = LdtkImport {
+                int_grid: Some("Tiles".to_owned()),
+                empty: Some("ldtk_wall".to_owned()),
+                ..default()
+            };
+            let level = project
+                .level::<LdtkTestRegistry>(&project.levels[0], &import)
+                .unwrap();
+            assert_eq!(level.size, IVec2::new(3, 2));
+            assert!(level.tiles == vec![wall, wall, wall, floor, wall, floor]);
+
+            assert!(matches!(
+                project.level::<LdtkTestRegistry>(&project.levels[0], &LdtkImport::default()),
+                Err(LdtkError::UnmappedValue {
+                    x: 1,
+                    y: 1,
+                    value: 0,
+                    ..
+                })
+            ));
+            assert!(matches!(
+                project.level::<LdtkTestRegistry>(&project.levels[1], &import),
+                Err(LdtkError::UnmappedValue {
+                    x: 1,
+                    y: 0,
+                    value: 3,
+                    ..
+                })
+            ));
+        });
+    }
+
+    #[test]
+    fn invalid_size() {
+        let app = app();
+        RegistryScope::of(&app.world).enter(|| {
+            let mut project = project();
+            let import = LdtkImport {
+                empty: Some("ldtk_wall".to_owned()),
+                ..default()
+            };
+            for (width, height) in [(2, 2), (0, 6), (65536, 65536)] {
+                let layer = &mut project.levels[0].layers[1];
+                layer.width = width;
+                layer.height = height;
+                assert!(matches!(
+                    project.level::<LdtkTestRegistry>(&project.levels[0], &import),
+                    Err(LdtkError::InvalidSize { values: 6, .. })
+                ));
+            }
+        });
+    }
+
+    #[test]
+    fn missing_layer() {
+        let app = app();
+        RegistryScope::of(&app.world).enter(|| {
+            let project = project();
+            assert!(matches!(
+                project.level::<LdtkTestRegistry>(&project.levels[2], &LdtkImport::default()),
+                Err(LdtkError::MissingLayer { layer: None, .. })
+            ));
+            let import = LdtkImport {
+                int_grid: Some("Walls".to_owned()),
+                ..default()
+            };
+            assert!(matches!(
+                project.level::<LdtkTestRegistry>(&project.levels[0], &import),
+                Err(LdtkError::MissingLayer {
+                    layer: Some(layer),
+                    ..
+                }) if layer == "Walls"
+            ));
+        });
+    }
+
+    #[test]
+    fn external_level() {
+        let app = app();
+        RegistryScope::of(&app.world).enter(|| {
+            let project = project();
+            assert_eq!(
+                project.levels[3].external.as_deref(),
+                Some(Path::new("project/Outside.ldtkl"))
+            );
+            assert!(matches!(
+                project.level::<LdtkTestRegistry>(&project.levels[3], &LdtkImport::default()),
+                Err(LdtkError::External(level)) if level == "Outside"
+            ));
+        });
+    }
+
+    #[test]
+    fn spawn_markers() {
+        let project = project();
+        let markers = project
+            .level_by_identifier("ldtk_cave")
+            .unwrap()
+            .spawn_markers()
+            .collect::<Vec<_>>();
+        assert_eq!(markers.len(), 1);
+        assert_eq!(markers[0].identifier, "Player");
+        assert_eq!(markers[0].pos, IVec2::new(1, 0));
+        assert_eq!(markers[0].fields.len(), 2);
+        assert_eq!(markers[0].fields["hp"], "10");
+        assert_eq!(markers[0].fields["name"], "hero");
+    }
+
+    #[test]
+    fn loader() {
+        let dir = Dir::default();
+        dir.insert_asset_text(Path::new("maps/project.ldtk"), PROJECT);
+        dir.insert_asset_text(Path::new("maps/project/Outside.ldtkl"), OUTSIDE);
+        let reader = MemoryAssetReader { root: dir };
+
+        let mut app = app();
+        app.register_asset_source(
+            AssetSourceId::Default,
+            AssetSource::build().with_reader(move || Box::new(reader.clone())),
+        )
+        .add_plugins((
+            TaskPoolPlugin::default(),
+            AssetPlugin::default(),
+            LdtkPlugin,
+        ));
+
+        let handle = app
+            .world
+            .resource::<AssetServer>()
+            .load::<LdtkProject>("maps/project.ldtk");
+        for _ in 0..1000 {
+            app.update();
+            if app
+                .world
+                .resource::<Assets<LdtkProject>>()
+                .contains(&handle)
+            {
+                break;
+            }
+            std::thread::sleep(Duration::from_millis(1));
+        }
+
+        let projects = app.world.resource::<Assets<LdtkProject>>();
+        let project = projects.get(&handle).unwrap();
+        let outside = project.level_by_identifier("Outside").unwrap();
+        assert!(outside.external.is_none());
+        let level = RegistryScope::of(&app.world)
+            .enter(|| project.level::<LdtkTestRegistry>(outside, &LdtkImport::default()))
+            .unwrap();
+        assert!(level.tiles == vec![RegistryId::new::<LdtkFloor>()]);
+        assert!(level.kind.is::<LdtkCave>());
+    }
+}
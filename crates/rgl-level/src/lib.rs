@@ -1,16 +1,31 @@
-use std::marker::PhantomData;
+mod ascii;
+mod bsp;
+mod cave;
+mod ldtk;
+mod tiled;
 
-use bevy::prelude::*;
+use std::{fmt, marker::PhantomData, sync::Arc};
+
+use bevy::{prelude::*, reflect::ReflectRef};
 use bevy_ecs_tilemap::prelude::*;
 use fastrand::Rng;
 use rgl_registry::*;
+use serde::{Deserialize, Serialize};
+
+pub use ascii::*;
+pub use bsp::*;
+pub use cave::*;
+pub use ldtk::*;
+pub use tiled::*;
 
 pub struct LevelPlugin;
 
 impl Plugin for LevelPlugin {
     fn build(&self, app: &mut App) {
-        app.register_two_sided_data_id2value::<LevelKindRegistry, &'static str>("level_kind")
-            .register_two_sided_data_id2value::<DefaultLevel, &'static str>("default");
+        app.register_name::<LevelKindRegistry>("level_kind")
+            .register_name::<DefaultLevel>("default")
+            .register_type::<Vec<RegistryId<LevelKindRegistry>>>()
+            .register_type::<LevelSpawnMarker>();
     }
 }
 
@@ -18,7 +33,11 @@ pub struct LayerPlugin<R: Registry>(PhantomData<R>);
 
 impl<R: Registry> Plugin for LayerPlugin<R> {
     fn build(&self, app: &mut App) {
-        app.add_systems(PostUpdate, generate_layers::<R>);
+        app.add_systems(PostUpdate, generate_layers::<R>)
+            .register_type::<Level<R>>()
+            .register_type::<Layer<R>>()
+            .register_type::<RegistryId<R>>()
+            .register_type::<Vec<RegistryId<R>>>();
     }
 }
 
@@ -85,7 +104,9 @@ impl Default for LevelObjectRarity {
     }
 }
 
-#[derive(Component)]
+/// The objects are not reflected, they must be added again after the layer is loaded from a scene
+#[derive(Component, Reflect)]
+#[reflect(Component)]
 pub struct Layer<R: Registry> {
     /// Empty vector means, that the layer should be created for each level kind
     pub level_kinds: Vec<RegistryId<LevelKindRegistry>>,
@@ -93,6 +114,7 @@ pub struct Layer<R: Registry> {
     pub tile_size: TilemapTileSize,
     pub grid_size: TilemapGridSize,
     pub texture: TilemapTexture,
+    #[reflect(ignore)]
     objects: Vec<(Box<dyn LevelObjectDyn<R>>, LevelObjectRarity)>,
 }
 
@@ -211,13 +233,97 @@ impl<R: Registry> Layer<R> {
     }
 }
 
-#[derive(Component)]
+/// Serialized with the names of the tiles and the kind, see [`RegistryNameCell`].
+/// It is reflected the same way, so a level entity can be saved in a scene.
+/// Deserialization and [`FromReflect`] fail if the amount of the tiles doesn't match the size.
+/// Can be loaded as an asset from a text map, see [`LevelAsciiPlugin`]
+#[derive(Component, Asset, Reflect, Serialize, Deserialize)]
+#[reflect(Component, from_reflect = false)]
+#[serde(bound = "", try_from = "LevelData<R>")]
 pub struct Level<R: Registry> {
     pub tiles: Vec<RegistryId<R>>,
     pub kind: RegistryId<LevelKindRegistry>,
     pub size: IVec2,
 }
 
+/// An empty level, the derived [`ReflectComponent`] applies the reflected level to it
+impl<R: Registry> FromWorld for Level<R> {
+    fn from_world(_world: &mut World) -> Self {
+        Self {
+            tiles: Vec::new(),
+            kind: RegistryId::new::<DefaultLevel>(),
+            size: IVec2::ZERO,
+        }
+    }
+}
+
+impl<R: Registry> FromReflect for Level<R> {
+    fn from_reflect(reflect: &dyn Reflect) -> Option<Self> {
+        let ReflectRef::Struct(level) = reflect.reflect_ref() else {
+            return None;
+        };
+        let level = Self {
+            tiles: FromReflect::from_reflect(level.field("tiles")?)?,
+            kind: FromReflect::from_reflect(level.field("kind")?)?,
+            size: FromReflect::from_reflect(level.field("size")?)?,
+        };
+        check_size(level.size, level.tiles.len()).ok()?;
+        Some(level)
+    }
+}
+
+#[derive(Deserialize)]
+#[serde(bound = "")]
+struct LevelData<R: Registry> {
+    tiles: Vec<RegistryId<R>>,
+    kind: RegistryId<LevelKindRegistry>,
+    size: IVec2,
+}
+
+impl<R: Registry> TryFrom<LevelData<R>> for Level<R> {
+    type Error = LevelSizeError;
+
+    fn try_from(value: LevelData<R>) -> Result<Self, Self::Error> {
+        check_size(value.size, value.tiles.len())?;
+        Ok(Self {
+            tiles: value.tiles,
+            kind: value.kind,
+            size: value.size,
+        })
+    }
+}
+
+fn check_size(size: IVec2, tiles: usize) -> Result<(), LevelSizeError> {
+    let expected = size
+        .cmpge(IVec2::ZERO)
+        .all()
+        .then(|| (size.x as usize).checked_mul(size.y as usize))
+        .flatten();
+    match expected == Some(tiles) {
+        true => Ok(()),
+        false => Err(LevelSizeError { size, tiles }),
+    }
+}
+
+/// The amount of the tiles of a deserialized level doesn't match its size
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub struct LevelSizeError {
+    pub size: IVec2,
+    pub tiles: usize,
+}
+
+impl fmt::Display for LevelSizeError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(
+            f,
+            "Level of size {} can not have {} tiles",
+            self.size, self.tiles
+        )
+    }
+}
+
+impl std::error::Error for LevelSizeError {}
+
 impl<R: Registry> Level<R> {
     pub fn from_tiles<const COLUMNS: usize, const ROWS: usize>(
         tiles: [[RegistryId<R>; COLUMNS]; ROWS],
@@ -244,7 +350,7 @@ impl<R: Registry> Level<R> {
     pub fn iter(&self) -> impl Iterator<Item = (IVec2, RegistryId<R>)> + '_ {
         self.tiles.iter().cloned().enumerate().map(|(index, tile)| {
             (
-                IVec2::new(index as i32 % self.size.x, index as i32 / self.size.y),
+                IVec2::new(index as i32 % self.size.x, index as i32 / self.size.x),
                 tile,
             )
         })
@@ -254,7 +360,9 @@ impl<R: Registry> Level<R> {
         if pos.x >= self.size.x || pos.x <= -1 || pos.y >= self.size.y || pos.y <= -1 {
             None
         } else {
-            Some(self.tiles[(pos.x + pos.y * self.size.x) as usize].clone())
+            self.tiles
+                .get((pos.x + pos.y * self.size.x) as usize)
+                .cloned()
         }
     }
 }
@@ -284,16 +392,58 @@ pub trait LevelObject<R: Registry>: Sync + Send + 'static {
     fn check(&self, level: &Level<R>, pos: IVec2, fill: &mut Vec<IVec2>) -> bool;
 }
 
+/// A tile of the pattern of [`DefaultLevelObject`], can be either one tile or a group of them
+pub enum LevelTilePattern<R: Registry> {
+    Id(RegistryId<R>),
+    Filter(Arc<dyn RegistryIdFilter<R>>),
+}
+
+impl<R: Registry> LevelTilePattern<R> {
+    /// Creates a pattern, that matches every member of the tag.
+    /// The tag doesn't change after the registries are frozen, so the pattern must be created after
+    /// [`RegistrySet`], e.g. in [`Startup`], to match every member
+    pub fn tag<T: RegistryTag<Registry = R>>(tag: &RegistryTagSet<T>) -> Self {
+        Self::Filter(Arc::new(tag.clone()))
+    }
+
+    /// Creates a pattern, that matches the tile and its variants, see [`RegistryId::is_a`]
+    pub fn is_a(parent: RegistryId<R>) -> Self {
+        Self::Filter(Arc::new(RegistryIsA(parent)))
+    }
+
+    pub fn matches(&self, tile: &RegistryId<R>) -> bool {
+        match self {
+            Self::Id(id) => id.eq(tile),
+            Self::Filter(filter) => filter.contains(tile),
+        }
+    }
+}
+
+impl<R: Registry> From<RegistryId<R>> for LevelTilePattern<R> {
+    fn from(value: RegistryId<R>) -> Self {
+        Self::Id(value)
+    }
+}
+
+impl<R: Registry> Clone for LevelTilePattern<R> {
+    fn clone(&self) -> Self {
+        match self {
+            Self::Id(id) => Self::Id(id.clone()),
+            Self::Filter(filter) => Self::Filter(filter.clone()),
+        }
+    }
+}
+
 pub struct DefaultLevelObject<R: Registry, B> {
     pub level_kind: Option<RegistryId<LevelKindRegistry>>,
-    pub tiles: [Option<RegistryId<R>>; 9],
+    pub tiles: [Option<LevelTilePattern<R>>; 9],
     pub bundle: B,
 }
 
 impl<R: Registry, B> DefaultLevelObject<R, B> {
     pub fn new(
         level_kind: Option<RegistryId<LevelKindRegistry>>,
-        tiles: [Option<RegistryId<R>>; 9],
+        tiles: [Option<LevelTilePattern<R>>; 9],
         bundle: B,
     ) -> Self {
         Self {
@@ -326,9 +476,9 @@ where
         let mut i = 0;
         for dy in -1..2 {
             for dx in -1..2 {
-                if let Some(rid) = &self.tiles[i] {
+                if let Some(pattern) = &self.tiles[i] {
                     let c_pos = pos + IVec2::new(dx, dy);
-                    if !matches!(level.get(c_pos), Some(l_tile) if l_tile.eq(rid)) {
+                    if !matches!(level.get(c_pos), Some(l_tile) if pattern.matches(&l_tile)) {
                         return false;
                     }
                 }
@@ -379,3 +529,165 @@ impl<R: Registry, T: LevelObject<R>> LevelObjectDyn<R> for T {
         T::check(&self, level, pos, fill)
     }
 }
+
+#[cfg(test)]
+mod tests {
+    use bevy::scene::serde::SceneDeserializer;
+    use serde::de::DeserializeSeed;
+
+    use super::*;
+
+    new_registry!(PatternTestRegistry, u8);
+    new_registry_items!(PatternTestRegistry {
+        PatternWall,
+        PatternFloor,
+        PatternGrass,
+        PatternWater
+    });
+    new_registry_tags!(PatternTestRegistry { PatternWalkableTag });
+
+    fn object(
+        center: LevelTilePattern<PatternTestRegistry>,
+    ) -> DefaultLevelObject<PatternTestRegistry, ()> {
+        let mut tiles: [Option<LevelTilePattern<_>>; 9] = Default::default();
+        tiles[4] = Some(center);
+        DefaultLevelObject::new(None, tiles, ())
+    }
+
+    fn matches(object: &DefaultLevelObject<PatternTestRegistry, ()>) -> Vec<IVec2> {
+        let level = Level::from_tiles([
+            [
+                RegistryId::new::<PatternWall>(),
+                RegistryId::new::<PatternFloor>(),
+            ],
+            [
+                RegistryId::new::<PatternGrass>(),
+                RegistryId::new::<PatternWater>(),
+            ],
+        ]);
+        let mut fill = vec![];
+        for (pos, _) in level.iter() {
+            LevelObject::check(object, &level, pos, &mut fill);
+        }
+        fill
+    }
+
+    #[test]
+    fn tag_pattern() {
+        let mut app = App::new();
+        app.add_to_tag::<PatternWalkableTag, PatternFloor>()
+            .add_to_tag::<PatternWalkableTag, PatternGrass>();
+        app.update();
+
+        let tag = app.world.resource::<RegistryTagSet<PatternWalkableTag>>();
+        let walkable = object(LevelTilePattern::tag(tag));
+        assert_eq!(matches(&walkable), [IVec2::new(1, 0), IVec2::new(0, 1)]);
+        let water = object(RegistryId::new::<PatternWater>().into());
+        assert_eq!(matches(&water), [IVec2::new(1, 1)]);
+    }
+
+    #[test]
+    fn scene_round_trip() {
+        let mut app = App::new();
+        app.add_plugins((LevelPlugin, LayerPlugin::<PatternTestRegistry>::default()))
+            .register_name::<PatternWall>("pattern_wall")
+            .register_name::<PatternFloor>("pattern_floor")
+            .register_name::<PatternGrass>("pattern_grass")
+            .register_name::<PatternWater>("pattern_water")
+            .register_type::<TilemapTileSize>()
+            .register_type::<TilemapGridSize>()
+            .register_type::<TilemapTexture>()
+            .register_type::<IVec2>();
+        app.update();
+
+        let level = Level::<PatternTestRegistry>::from_tiles([
+            [
+                RegistryId::new::<PatternWall>(),
+                RegistryId::new::<PatternFloor>(),
+            ],
+            [
+                RegistryId::new::<PatternGrass>(),
+                RegistryId::new::<PatternWater>(),
+            ],
+        ]);
+        let tiles = level.tiles.clone();
+        let layer = Layer::<PatternTestRegistry> {
+            level_kinds: vec![RegistryId::new::<DefaultLevel>()],
+            z_index: 2.0,
+            tile_size: TilemapTileSize { x: 16.0, y: 8.0 },
+            ..Default::default()
+        };
+        let level_entity = app.world.spawn(level).id();
+        let layer_entity = app.world.spawn(layer).id();
+
+        RegistryScope::of(&app.world).enter(|| {
+            let type_registry = app.world.resource::<AppTypeRegistry>().clone();
+            let ron = DynamicScene::from_world(&app.world)
+                .serialize_ron(&type_registry)
+                .unwrap();
+            assert!(ron.contains("\"pattern_water\""));
+            assert!(ron.contains("\"default\""));
+
+            let mut deserializer = ron::Deserializer::from_str(&ron).unwrap();
+            let scene = SceneDeserializer {
+                type_registry: &type_registry.read(),
+            }
+            .deserialize(&mut deserializer)
+            .unwrap();
+            app.world.despawn(level_entity);
+            app.world.despawn(layer_entity);
+            scene
+                .write_to_world(&mut app.world, &mut Default::default())
+                .unwrap();
+
+            let mut levels = app.world.query::<&Level<PatternTestRegistry>>();
+            let level = levels.single(&app.world);
+            assert!(level.tiles == tiles);
+            assert!(level.kind == RegistryId::new::<DefaultLevel>());
+            assert_eq!(level.size, IVec2::new(2, 2));
+            let mut layers = app.world.query::<&Layer<PatternTestRegistry>>();
+            let layer = layers.single(&app.world);
+            assert!(layer.level_kinds == [RegistryId::new::<DefaultLevel>()]);
+            assert_eq!(layer.z_index, 2.0);
+            assert_eq!(layer.tile_size, TilemapTileSize { x: 16.0, y: 8.0 });
+        });
+    }
+
+    #[test]
+    fn invalid_size() {
+        let mut app = App::new();
+        app.add_plugins(LevelPlugin)
+            .register_name::<PatternWall>("pattern_wall");
+        app.update();
+
+        RegistryScope::of(&app.world).enter(|| {
+            let level = |size| {
+                ron::from_str::<Level<PatternTestRegistry>>(&format!(
+                    r#"(tiles: ["pattern_wall", "pattern_wall"], kind: "default", size: {})"#,
+                    size
+                ))
+            };
+            assert!(level("(2, 1)").is_ok_and(|level| level.get(IVec2::new(1, 0)).is_some()));
+            assert!(level("(1, 1)").is_err());
+            assert!(level("(-2, -1)").is_err());
+        });
+
+        let level = Level::<PatternTestRegistry>::from_tiles([[RegistryId::new::<PatternWall>()]]);
+        let mut reflected = level.clone_dynamic();
+        assert!(Level::<PatternTestRegistry>::from_reflect(&reflected).is_some());
+        reflected
+            .field_mut("size")
+            .unwrap()
+            .apply(&IVec2::new(2, 1));
+        assert!(Level::<PatternTestRegistry>::from_reflect(&reflected).is_none());
+    }
+
+    #[test]
+    #[should_panic(expected = "can not be registered after the registries were frozen")]
+    fn tag_after_freeze() {
+        let mut app = App::new();
+        app.add_to_tag::<PatternWalkableTag, PatternFloor>();
+        app.update();
+        app.add_to_tag::<PatternWalkableTag, PatternGrass>();
+    }
+}
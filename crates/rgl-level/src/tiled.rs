@@ -0,0 +1,1098 @@
+use std::{
+    collections::BTreeMap,
+    ffi::OsStr,
+    fmt,
+    io::Read,
+    path::{Path, PathBuf},
+};
+
+use base64::{engine::general_purpose::STANDARD, Engine};
+use bevy::{
+    asset::{
+        io::{file::FileAssetReader, Reader},
+        AssetLoader, AsyncReadExt, LoadContext, ReadAssetBytesError,
+    },
+    prelude::*,
+    reflect::TypePath,
+    utils::BoxedFuture,
+};
+use bevy_ecs_tilemap::prelude::*;
+use flate2::read::{GzDecoder, ZlibDecoder};
+use rgl_registry::*;
+use serde::Deserialize;
+use xml::reader::{EventReader, XmlEvent};
+
+use crate::{DefaultLevel, Layer, Level};
+
+/// Global ids of the tiles keep the flip flags in the highest bits
+const GID_MASK: u32 = 0x0fff_ffff;
+
+/// A map exported from Tiled as TMX (XML) or JSON.
+///
+/// The tile layers can be stored as CSV, XML or base64, the base64 data can be compressed
+/// with zlib or gzip. Zstandard compression and infinite maps are not supported.
+/// Every tile layer becomes a [`Level`], where the tiles are mapped to the registry items
+/// by the property of the tile in the tileset, see [`TiledImport`]
+#[derive(Asset, TypePath, Clone, Debug, Default, PartialEq, Eq)]
+pub struct TiledMap {
+    pub width: u32,
+    pub height: u32,
+    pub tile_width: u32,
+    pub tile_height: u32,
+    pub tilesets: Vec<TiledTileset>,
+    pub layers: Vec<TiledLayer>,
+}
+
+#[derive(Clone, Debug, Default, PartialEq, Eq)]
+pub struct TiledTileset {
+    pub first_gid: u32,
+    /// Path of the external tileset, it is not loaded yet if it is set
+    pub source: Option<PathBuf>,
+    pub name: String,
+    pub tile_width: u32,
+    pub tile_height: u32,
+    /// Path of the image of the tileset, is relative to the file of the tileset
+    pub image: Option<PathBuf>,
+    /// Properties of the tiles by their ids in the tileset
+    pub tiles: BTreeMap<u32, BTreeMap<String, String>>,
+}
+
+/// A tile layer, the tiles are stored row by row starting from the top one
+#[derive(Clone, Debug, Default, PartialEq, Eq)]
+pub struct TiledLayer {
+    pub name: String,
+    pub width: u32,
+    pub height: u32,
+    /// Global ids of the tiles, 0 means no tile
+    pub tiles: Vec<u32>,
+}
+
+/// How the tiles of [`TiledMap`] are turned into registry items
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub struct TiledImport {
+    /// The property of the tile with the name of the registry item, `name` by default
+    pub property: String,
+    /// Name of the item used for the cells without a tile
+    pub empty: Option<String>,
+    /// Name of the level kind, [`DefaultLevel`] is used if it is not set
+    pub kind: Option<String>,
+}
+
+impl Default for TiledImport {
+    fn default() -> Self {
+        Self {
+            property: "name".to_owned(),
+            empty: None,
+            kind: None,
+        }
+    }
+}
+
+#[derive(Debug)]
+pub enum TiledError {
+    Io(PathBuf, std::io::Error),
+    /// The asset server couldn't read the external tileset
+    Read(PathBuf, ReadAssetBytesError),
+    Xml(xml::reader::Error),
+    Json(serde_json::Error),
+    Base64(base64::DecodeError),
+    Decompress(std::io::Error),
+    UnknownExtension(PathBuf),
+    /// The file is not a valid Tiled file
+    Invalid(String),
+    /// The feature of Tiled, like zstd compression or infinite maps, is not supported
+    Unsupported(String),
+    /// The tile has no item property or the cell is empty and [`TiledImport::empty`] is not set
+    UnmappedTile {
+        layer: String,
+        x: u32,
+        y: u32,
+        gid: u32,
+    },
+    /// The item is not registered in the [`RegistryNameCell`]
+    UnknownItem(String),
+}
+
+impl fmt::Display for TiledError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            Self::Io(path, err) => write!(f, "Couldn't read {}: {}", path.display(), err),
+            Self::Read(path, err) => write!(f, "Couldn't read {}: {}", path.display(), err),
+            Self::Xml(err) => write!(f, "Couldn't parse XML: {}", err),
+            Self::Json(err) => write!(f, "Couldn't parse JSON: {}", err),
+            Self::Base64(err) => write!(f, "Couldn't decode base64 tiles: {}", err),
+            Self::Decompress(err) => write!(f, "Couldn't decompress tiles: {}", err),
+            Self::UnknownExtension(path) => write!(
+                f,
+                "Unknown extension of {}, expected .tmx, .tsx, .tmj, .tsj or .json",
+                path.display()
+            ),
+            Self::Invalid(msg) => write!(f, "Invalid Tiled file: {}", msg),
+            Self::Unsupported(msg) => write!(f, "Unsupported Tiled feature: {}", msg),
+            Self::UnmappedTile { layer, x, y, gid } => write!(
+                f,
+                "Tile {} at {}, {} of layer {} has no registry item",
+                gid, x, y, layer
+            ),
+            Self::UnknownItem(name) => write!(f, "Registry item {} is not registered", name),
+        }
+    }
+}
+
+impl std::error::Error for TiledError {}
+
+impl TiledMap {
+    pub fn from_tmx(s: &str) -> Result<Self, TiledError> {
+        let map = XmlElement::parse(s)?;
+        if map.name != "map" {
+            return Err(TiledError::Invalid(format!(
+                "expected map element, found {}",
+                map.name
+            )));
+        }
+        if map.attr("infinite") == Some("1") {
+            return Err(TiledError::Unsupported("infinite maps".to_owned()));
+        }
+        Ok(Self {
+            width: map.parse_attr("width")?,
+            height: map.parse_attr("height")?,
+            tile_width: map.parse_attr("tilewidth")?,
+            tile_height: map.parse_attr("tileheight")?,
+            tilesets: map
+                .children("tileset")
+                .map(TiledTileset::from_element)
+                .collect::<Result<_, _>>()?,
+            layers: map
+                .children("layer")
+                .map(TiledLayer::from_element)
+                .collect::<Result<_, _>>()?,
+        })
+    }
+
+    pub fn from_json(s: &str) -> Result<Self, TiledError> {
+        let map: JsonMap = serde_json::from_str(s).map_err(TiledError::Json)?;
+        if map.infinite {
+            return Err(TiledError::Unsupported("infinite maps".to_owned()));
+        }
+        Ok(Self {
+            width: map.width,
+            height: map.height,
+            tile_width: map.tilewidth,
+            tile_height: map.tileheight,
+            tilesets: map.tilesets.into_iter().map(TiledTileset::from).collect(),
+            layers: map
+                .layers
+                .into_iter()
+                .filter(|layer| layer.ty == "tilelayer")
+                .map(TiledLayer::try_from)
+                .collect::<Result<_, _>>()?,
+        })
+    }
+
+    /// Reads the map from the assets directory, the path is relative to it.
+    /// The external tilesets are loaded too, their paths are relative to the map.
+    /// Reads the files directly, use [`TiledMapLoader`] to load the map with the asset server
+    pub fn load(path: impl AsRef<Path>) -> Result<Self, TiledError> {
+        let path = path.as_ref();
+        let mut map = Self::parse(path, &read_asset(path)?)?;
+        let dir = path.parent().unwrap_or(Path::new(""));
+        for tileset in map.tilesets.iter_mut() {
+            match tileset.source.take() {
+                Some(source) => {
+                    let path = dir.join(source);
+                    tileset.set_external(TiledTileset::parse(&path, &read_asset(&path)?)?);
+                }
+                None => tileset.image = tileset.image.take().map(|image| dir.join(image)),
+            }
+        }
+        Ok(map)
+    }
+
+    /// Parses the map by the extension of its path
+    fn parse(path: &Path, s: &str) -> Result<Self, TiledError> {
+        match path.extension().and_then(OsStr::to_str) {
+            Some("tmx") => Self::from_tmx(s),
+            Some("tmj" | "json") => Self::from_json(s),
+            _ => Err(TiledError::UnknownExtension(path.to_path_buf())),
+        }
+    }
+
+    /// Returns the tileset and the id of the tile in it
+    pub fn tileset_of(&self, gid: u32) -> Option<(&TiledTileset, u32)> {
+        let gid = gid & GID_MASK;
+        self.tilesets
+            .iter()
+            .filter(|tileset| tileset.first_gid <= gid)
+            .max_by_key(|tileset| tileset.first_gid)
+            .map(|tileset| (tileset, gid - tileset.first_gid))
+    }
+
+    /// Returns the properties of the tile with the global id
+    pub fn tile_properties(&self, gid: u32) -> Option<&BTreeMap<String, String>> {
+        let (tileset, id) = self.tileset_of(gid)?;
+        tileset.tiles.get(&id)
+    }
+
+    /// Creates a level of the tile layer
+    pub fn level<R: Registry>(
+        &self,
+        layer: &TiledLayer,
+        import: &TiledImport,
+    ) -> Result<Level<R>, TiledError> {
+        let empty = import
+            .empty
+            .as_ref()
+            .map(|name| item_by_name::<R>(name))
+            .transpose()?;
+        let kind = match &import.kind {
+            Some(name) => item_by_name(name)?,
+            None => RegistryId::new::<DefaultLevel>(),
+        };
+        let tiles = layer
+            .tiles
+            .iter()
+            .enumerate()
+            .map(|(index, &gid)| {
+                let unmapped = || TiledError::UnmappedTile {
+                    layer: layer.name.clone(),
+                    x: index as u32 % layer.width,
+                    y: index as u32 / layer.width,
+                    gid,
+                };
+                if gid & GID_MASK == 0 {
+                    return empty.clone().ok_or_else(unmapped);
+                }
+                let name = self
+                    .tile_properties(gid)
+                    .and_then(|properties| properties.get(&import.property))
+                    .ok_or_else(unmapped)?;
+                item_by_name(name)
+            })
+            .collect::<Result<Vec<_>, _>>()?;
+        Ok(Level {
+            tiles,
+            kind,
+            size: IVec2::new(layer.width as i32, layer.height as i32),
+        })
+    }
+
+    /// Creates a level of every tile layer with the names of the layers
+    pub fn levels<R: Registry>(
+        &self,
+        import: &TiledImport,
+    ) -> Result<Vec<(String, Level<R>)>, TiledError> {
+        self.layers
+            .iter()
+            .map(|layer| Ok((layer.name.clone(), self.level(layer, import)?)))
+            .collect()
+    }
+
+    /// Creates a layer with the sizes of the tileset and the map and the texture of the tileset,
+    /// the objects of the layer must be added separately
+    pub fn layer<R: Registry>(
+        &self,
+        tileset: &TiledTileset,
+        asset_server: &AssetServer,
+    ) -> Layer<R> {
+        Layer {
+            tile_size: Vec2::new(tileset.tile_width as f32, tileset.tile_height as f32).into(),
+            grid_size: Vec2::new(self.tile_width as f32, self.tile_height as f32).into(),
+            texture: match &tileset.image {
+                Some(image) => TilemapTexture::Single(asset_server.load(image.clone())),
+                None => TilemapTexture::default(),
+            },
+            ..Default::default()
+        }
+    }
+}
+
+fn item_by_name<R: Registry>(name: &str) -> Result<RegistryId<R>, TiledError> {
+    RegistryId::from_name(name).ok_or_else(|| TiledError::UnknownItem(name.to_owned()))
+}
+
+fn read_asset(path: &Path) -> Result<String, TiledError> {
+    let full_path = FileAssetReader::get_base_path().join("assets").join(path);
+    std::fs::read_to_string(&full_path).map_err(|err| TiledError::Io(full_path, err))
+}
+
+fn utf8(path: &Path, bytes: Vec<u8>) -> Result<String, TiledError> {
+    String::from_utf8(bytes)
+        .map_err(|_| TiledError::Invalid(format!("{} is not UTF-8", path.display())))
+}
+
+/// Loads `.tmx` and `.tmj` files as [`TiledMap`]s with the external tilesets
+#[derive(Default)]
+pub struct TiledMapLoader;
+
+impl AssetLoader for TiledMapLoader {
+    type Asset = TiledMap;
+    type Settings = ();
+    type Error = TiledError;
+
+    fn load<'a>(
+        &'a self,
+        reader: &'a mut Reader,
+        _settings: &'a Self::Settings,
+        load_context: &'a mut LoadContext,
+    ) -> BoxedFuture<'a, Result<Self::Asset, Self::Error>> {
+        Box::pin(async move {
+            let path = load_context.path().to_owned();
+            let mut bytes = Vec::new();
+            reader
+                .read_to_end(&mut bytes)
+                .await
+                .map_err(|err| TiledError::Io(path.clone(), err))?;
+            let mut map = TiledMap::parse(&path, &utf8(&path, bytes)?)?;
+            let dir = path.parent().unwrap_or(Path::new(""));
+            for tileset in map.tilesets.iter_mut() {
+                match tileset.source.take() {
+                    Some(source) => {
+                        let path = dir.join(source);
+                        let bytes = load_context
+                            .read_asset_bytes(path.clone())
+                            .await
+                            .map_err(|err| TiledError::Read(path.clone(), err))?;
+                        tileset.set_external(TiledTileset::parse(&path, &utf8(&path, bytes)?)?);
+                    }
+                    None => tileset.image = tileset.image.take().map(|image| dir.join(image)),
+                }
+            }
+            Ok(map)
+        })
+    }
+
+    fn extensions(&self) -> &[&str] {
+        &["tmx", "tmj"]
+    }
+}
+
+/// Adds [`TiledMap`] asset and [`TiledMapLoader`]
+pub struct TiledPlugin;
+
+impl Plugin for TiledPlugin {
+    fn build(&self, app: &mut App) {
+        app.init_asset::<TiledMap>()
+            .register_asset_loader(TiledMapLoader);
+    }
+}
+
+impl TiledTileset {
+    pub fn from_tsx(s: &str) -> Result<Self, TiledError> {
+        Self::from_element(&XmlElement::parse(s)?)
+    }
+
+    pub fn from_json(s: &str) -> Result<Self, TiledError> {
+        serde_json::from_str::<JsonTileset>(s)
+            .map(Self::from)
+            .map_err(TiledError::Json)
+    }
+
+    /// Reads the tileset from the assets directory, the path of the image becomes relative to it
+    pub fn load(path: impl AsRef<Path>) -> Result<Self, TiledError> {
+        let path = path.as_ref();
+        Self::parse(path, &read_asset(path)?)
+    }
+
+    /// Parses the tileset by the extension of its path, the path of the image becomes relative to the assets
+    fn parse(path: &Path, s: &str) -> Result<Self, TiledError> {
+        let mut tileset = match path.extension().and_then(OsStr::to_str) {
+            Some("tsx") => Self::from_tsx(s)?,
+            Some("tsj" | "json") => Self::from_json(s)?,
+            _ => return Err(TiledError::UnknownExtension(path.to_path_buf())),
+        };
+        let dir = path.parent().unwrap_or(Path::new(""));
+        tileset.image = tileset.image.map(|image| dir.join(image));
+        Ok(tileset)
+    }
+
+    /// Replaces the reference to the external tileset with it, the first global id is kept
+    fn set_external(&mut self, external: Self) {
+        *self = Self {
+            first_gid: self.first_gid,
+            ..external
+        };
+    }
+
+    fn from_element(element: &XmlElement) -> Result<Self, TiledError> {
+        let first_gid = match element.attr("firstgid") {
+            Some(_) => element.parse_attr("firstgid")?,
+            None => 1,
+        };
+        if let Some(source) = element.attr("source") {
+            return Ok(Self {
+                first_gid,
+                source: Some(source.into()),
+                ..Default::default()
+            });
+        }
+        Ok(Self {
+            first_gid,
+            source: None,
+            name: element.attr("name").unwrap_or_default().to_owned(),
+            tile_width: element.parse_attr("tilewidth")?,
+            tile_height: element.parse_attr("tileheight")?,
+            image: element
+                .children("image")
+                .next()
+                .and_then(|image| image.attr("source"))
+                .map(PathBuf::from),
+            tiles: element
+                .children("tile")
+                .map(|tile| {
+                    let properties = tile
+                        .children("properties")
+                        .flat_map(|properties| properties.children("property"))
+                        .map(|property| {
+                            let value = property.attr("value").unwrap_or(&property.text);
+                            (
+                                property.attr("name").unwrap_or_default().to_owned(),
+                                value.to_owned(),
+                            )
+                        })
+                        .collect();
+                    Ok((tile.parse_attr("id")?, properties))
+                })
+                .collect::<Result<_, TiledError>>()?,
+        })
+    }
+}
+
+impl TiledLayer {
+    fn from_element(element: &XmlElement) -> Result<Self, TiledError> {
+        let width = element.parse_attr("width")?;
+        let height = element.parse_attr("height")?;
+        let data = element
+            .children("data")
+            .next()
+            .ok_or_else(|| TiledError::Invalid("layer without data".to_owned()))?;
+        let compression = data.attr("compression");
+        let tiles = match data.attr("encoding") {
+            Some("csv") => parse_csv(&data.text)?,
+            Some("base64") => parse_base64(&data.text, compression)?,
+            Some(encoding) => {
+                return Err(TiledError::Unsupported(format!("{} encoding", encoding)))
+            }
+            None if compression.is_some() => {
+                return Err(TiledError::Invalid(
+                    "compressed layer without base64 encoding".to_owned(),
+                ))
+            }
+            None => data
+                .children("tile")
+                .map(|tile| match tile.attr("gid") {
+                    Some(_) => tile.parse_attr("gid"),
+                    None => Ok(0),
+                })
+                .collect::<Result<_, _>>()?,
+        };
+        Self::new(
+            element.attr("name").unwrap_or_default(),
+            width,
+            height,
+            tiles,
+        )
+    }
+
+    fn new(name: &str, width: u32, height: u32, tiles: Vec<u32>) -> Result<Self, TiledError> {
+        let count = width
+            .checked_mul(height)
+            .filter(|_| i32::try_from(width).is_ok() && i32::try_from(height).is_ok())
+            .ok_or_else(|| {
+                TiledError::Invalid(format!(
+                    "layer {} of {}x{} tiles is too large",
+                    name, width, height
+                ))
+            })?;
+        if tiles.len() != count as usize {
+            return Err(TiledError::Invalid(format!(
+                "layer {} has {} tiles, expected {}",
+                name,
+                tiles.len(),
+                count
+            )));
+        }
+        Ok(Self {
+            name: name.to_owned(),
+            width,
+            height,
+            tiles,
+        })
+    }
+}
+
+fn parse_csv(s: &str) -> Result<Vec<u32>, TiledError> {
+    s.split(',')
+        .map(str::trim)
+        .filter(|gid| !gid.is_empty())
+        .map(|gid| {
+            gid.parse()
+                .map_err(|_| TiledError::Invalid(format!("tile {} is not a number", gid)))
+        })
+        .collect()
+}
+
+fn parse_base64(s: &str, compression: Option<&str>) -> Result<Vec<u32>, TiledError> {
+    let data = STANDARD.decode(s.trim()).map_err(TiledError::Base64)?;
+    let mut bytes = vec![];
+    match compression {
+        None => bytes = data,
+        Some("zlib") => {
+            ZlibDecoder::new(data.as_slice())
+                .read_to_end(&mut bytes)
+                .map_err(TiledError::Decompress)?;
+        }
+        Some("gzip") => {
+            GzDecoder::new(data.as_slice())
+                .read_to_end(&mut bytes)
+                .map_err(TiledError::Decompress)?;
+        }
+        Some(compression) => {
+            return Err(TiledError::Unsupported(format!(
+                "{} compression",
+                compression
+            )))
+        }
+    }
+    if bytes.len() % 4 != 0 {
+        return Err(TiledError::Invalid(format!(
+            "base64 tiles have {} bytes, expected a multiple of 4",
+            bytes.len()
+        )));
+    }
+    Ok(bytes
+        .chunks_exact(4)
+        .map(|gid| u32::from_le_bytes([gid[0], gid[1], gid[2], gid[3]]))
+        .collect())
+}
+
+/// An element of XML document, the text of the element is the joined text of its content
+struct XmlElement {
+    name: String,
+    attributes: BTreeMap<String, String>,
+    children: Vec<XmlElement>,
+    text: String,
+}
+
+impl XmlElement {
+    /// Returns the root element
+    fn parse(s: &str) -> Result<Self, TiledError> {
+        let mut stack: Vec<XmlElement> = vec![];
+        for event in EventReader::from_str(s) {
+            match event.map_err(TiledError::Xml)? {
+                XmlEvent::StartElement {
+                    name, attributes, ..
+                } => stack.push(XmlElement {
+                    name: name.local_name,
+                    attributes: attributes
+                        .into_iter()
+                        .map(|attribute| (attribute.name.local_name, attribute.value))
+                        .collect(),
+                    children: vec![],
+                    text: String::new(),
+                }),
+                XmlEvent::EndElement { name } => {
+                    let element = stack.pop().ok_or_else(|| {
+                        TiledError::Invalid(format!(
+                            "{} element is closed, but not opened",
+                            name.local_name
+                        ))
+                    })?;
+                    match stack.last_mut() {
+                        Some(parent) => parent.children.push(element),
+                        None => return Ok(element),
+                    }
+                }
+                XmlEvent::Characters(text) | XmlEvent::CData(text) => {
+                    if let Some(element) = stack.last_mut() {
+                        element.text.push_str(&text);
+                    }
+                }
+                _ => {}
+            }
+        }
+        Err(TiledError::Invalid(
+            "document has no root element".to_owned(),
+        ))
+    }
+
+    fn attr(&self, name: &str) -> Option<&str> {
+        self.attributes.get(name).map(String::as_str)
+    }
+
+    fn parse_attr<T: std::str::FromStr>(&self, name: &str) -> Result<T, TiledError> {
+        let value = self.attr(name).ok_or_else(|| {
+            TiledError::Invalid(format!("{} element without {}", self.name, name))
+        })?;
+        value.parse().map_err(|_| {
+            TiledError::Invalid(format!(
+                "{} of {} element is invalid: {}",
+                name, self.name, value
+            ))
+        })
+    }
+
+    fn children<'a>(&'a self, name: &'a str) -> impl Iterator<Item = &'a XmlElement> + 'a {
+        self.children.iter().filter(move |child| child.name == name)
+    }
+}
+
+#[derive(Deserialize)]
+struct JsonMap {
+    width: u32,
+    height: u32,
+    tilewidth: u32,
+    tileheight: u32,
+    #[serde(default)]
+    infinite: bool,
+    #[serde(default)]
+    tilesets: Vec<JsonTileset>,
+    #[serde(default)]
+    layers: Vec<JsonLayer>,
+}
+
+#[derive(Deserialize)]
+struct JsonTileset {
+    #[serde(default = "default_first_gid")]
+    firstgid: u32,
+    source: Option<PathBuf>,
+    #[serde(default)]
+    name: String,
+    #[serde(default)]
+    tilewidth: u32,
+    #[serde(default)]
+    tileheight: u32,
+    image: Option<PathBuf>,
+    #[serde(default)]
+    tiles: Vec<JsonTile>,
+}
+
+fn default_first_gid() -> u32 {
+    1
+}
+
+#[derive(Deserialize)]
+struct JsonTile {
+    id: u32,
+    #[serde(default)]
+    properties: Vec<JsonProperty>,
+}
+
+#[derive(Deserialize)]
+struct JsonProperty {
+    name: String,
+    value: serde_json::Value,
+}
+
+#[derive(Deserialize)]
+struct JsonLayer {
+    #[serde(rename = "type")]
+    ty: String,
+    #[serde(default)]
+    name: String,
+    #[serde(default)]
+    width: u32,
+    #[serde(default)]
+    height: u32,
+    data: Option<serde_json::Value>,
+    encoding: Option<String>,
+    compression: Option<String>,
+}
+
+impl From<JsonTileset> for TiledTileset {
+    fn from(value: JsonTileset) -> Self {
+        Self {
+            first_gid: value.firstgid,
+            source: value.source,
+            name: value.name,
+            tile_width: value.tilewidth,
+            tile_height: value.tileheight,
+            image: value.image,
+            tiles: value
+                .tiles
+                .into_iter()
+                .map(|tile| {
+                    let properties = tile
+                        .properties
+                        .into_iter()
+                        .map(|property| {
+                            let value = match property.value {
+                                serde_json::Value::String(value) => value,
+                                value => value.to_string(),
+                            };
+                            (property.name, value)
+                        })
+                        .collect();
+                    (tile.id, properties)
+                })
+                .collect(),
+        }
+    }
+}
+
+impl TryFrom<JsonLayer> for TiledLayer {
+    type Error = TiledError;
+
+    fn try_from(value: JsonLayer) -> Result<Self, Self::Error> {
+        let compression = value.compression.as_deref().filter(|it| !it.is_empty());
+        let tiles = match (value.encoding.as_deref(), value.data) {
+            (Some("base64"), Some(serde_json::Value::String(data))) => {
+                parse_base64(&data, compression)?
+            }
+            (None | Some("csv"), Some(data)) if compression.is_none() => {
+                serde_json::from_value(data).map_err(TiledError::Json)?
+            }
+            _ => {
+                return Err(TiledError::Invalid(format!(
+                    "layer {} has no valid data",
+                    value.name
+                )))
+            }
+        };
+        Self::new(&value.name, value.width, value.height, tiles)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use std::{io::Write, time::Duration};
+
+    use bevy::{
+        asset::io::{
+            memory::{Dir, MemoryAssetReader},
+            AssetSource, AssetSourceId,
+        },
+        core::TaskPoolPlugin,
+    };
+    use flate2::{
+        write::{GzEncoder, ZlibEncoder},
+        Compression,
+    };
+
+    use super::*;
+
+    new_registry!(TiledTestRegistry, u8);
+    new_registry_items!(TiledTestRegistry {
+        TiledWall,
+        TiledFloor,
+        TiledWater,
+        TiledVoid,
+    });
+
+    const TILESETS: &str = r#"
+        <tileset firstgid="1" name="dungeon" tilewidth="16" tileheight="16">
+            <image source="dungeon.png" width="32" height="16"/>
+            <tile id="0"><properties><property name="name" value="tiled_wall"/></properties></tile>
+            <tile id="1"><properties><property name="name" value="tiled_floor"/></properties></tile>
+        </tileset>
+        <tileset firstgid="3" name="water" tilewidth="16" tileheight="16">
+            <tile id="0"><properties><property name="name">tiled_water</property></properties></tile>
+            <tile id="1"/>
+        </tileset>
+    "#;
+
+    fn app() -> App {
+        let mut app = App::new();
+        app.register_name::<TiledWall>("tiled_wall")
+            .register_name::<TiledFloor>("tiled_floor")
+            .register_name::<TiledWater>("tiled_water")
+            .register_name::<TiledVoid>("tiled_void")
+            .add_plugins(crate::LevelPlugin);
+        app
+    }
+
+    fn tmx(data: &str) -> String {
+        format!(
+            r#"<?xml version="1.0" encoding="UTF-8"?>
+            <map version="1.10" orientation="orthogonal" width="3" height="2" tilewidth="16" tileheight="16" infinite="0">
+                {}
+                <layer id="1" name="ground" width="3" height="2">{}</layer>
+            </map>"#,
+            TILESETS, data
+        )
+    }
+
+    fn base64(gids: &[u32], compression: Option<&str>) -> String {
+        let bytes = gids
+            .iter()
+            .flat_map(|gid| gid.to_le_bytes())
+            .collect::<Vec<_>>();
+        let bytes = match compression {
+            Some("zlib") => {
+                let mut encoder = ZlibEncoder::new(vec![], Compression::default());
+                encoder.write_all(&bytes).unwrap();
+                encoder.finish().unwrap()
+            }
+            Some("gzip") => {
+                let mut encoder = GzEncoder::new(vec![], Compression::default());
+                encoder.write_all(&bytes).unwrap();
+                encoder.finish().unwrap()
+            }
+            _ => bytes,
+        };
+        STANDARD.encode(bytes)
+    }
+
+    fn expected() -> Vec<RegistryId<TiledTestRegistry>> {
+        let wall = RegistryId::new::<TiledWall>();
+        let floor = RegistryId::new::<TiledFloor>();
+        let water = RegistryId::new::<TiledWater>();
+        vec![wall, floor, wall, floor, water, floor]
+    }
+
+    /// The second wall is flipped horizontally and the last floor is flipped diagonally
+    const GIDS: [u32; 6] = [1, 2, 0x8000_0001, 2, 3, 0x2000_0002];
+
+    #[test]
+    fn csv() {
+        let app = app();
+        RegistryScope::of(&app.world).enter(|| {
+            let map = TiledMap::from_tmx(&tmx(r#"<data encoding="csv">
+                    1,2,2147483649,
+                    2,3,536870914
+                </data>"#))
+            .unwrap();
+            assert_eq!(map.tilesets.len(), 2);
+            assert_eq!(map.tilesets[0].image, Some(PathBuf::from("dungeon.png")));
+            assert_eq!(map.layers[0].tiles, GIDS);
+            let levels = map
+                .levels::<TiledTestRegistry>(&TiledImport::default())
+                .unwrap();
+            assert_eq!(levels[0].0, "ground");
+            assert_eq!(levels[0].1.size, IVec2::new(3, 2));
+            assert!(levels[0].1.tiles == expected());
+        });
+    }
+
+    #[test]
+    fn base64_data() {
+        let app = app();
+        RegistryScope::of(&app.world).enter(|| {
+            for compression in [None, Some("zlib"), Some("gzip")] {
+                let data = format!(
+                    r#"<data encoding="base64"{}>{}</data>"#,
+                    compression
+                        .map(|compression| format!(r#" compression="{}""#, compression))
+                        .unwrap_or_default(),
+                    base64(&GIDS, compression)
+                );
+                let map = TiledMap::from_tmx(&tmx(&data)).unwrap();
+                assert_eq!(map.layers[0].tiles, GIDS);
+                let level = map
+                    .level::<TiledTestRegistry>(&map.layers[0], &TiledImport::default())
+                    .unwrap();
+                assert!(level.tiles == expected());
+            }
+            let data = format!(
+                r#"<data encoding="base64" compression="zstd">{}</data>"#,
+                base64(&GIDS, None)
+            );
+            assert!(matches!(
+                TiledMap::from_tmx(&tmx(&data)),
+                Err(TiledError::Unsupported(_))
+            ));
+        });
+    }
+
+    #[test]
+    fn tileset_of() {
+        let map = TiledMap::from_tmx(&tmx(r#"<data encoding="csv">1,2,3,4,1,1</data>"#)).unwrap();
+        let (tileset, id) = map.tileset_of(2).unwrap();
+        assert_eq!((tileset.name.as_str(), id), ("dungeon", 1));
+        let (tileset, id) = map.tileset_of(3).unwrap();
+        assert_eq!((tileset.name.as_str(), id), ("water", 0));
+        let (tileset, id) = map.tileset_of(0x4000_0004).unwrap();
+        assert_eq!((tileset.name.as_str(), id), ("water", 1));
+        assert!(map.tileset_of(0).is_none());
+        assert_eq!(
+            map.tile_properties(3).unwrap().get("name").unwrap(),
+            "tiled_water"
+        );
+        assert!(map.tile_properties(4).unwrap().is_empty());
+        assert!(map.tile_properties(5).is_none());
+    }
+
+    #[test]
+    fn unmapped_tile() {
+        let app = app();
+        RegistryScope::of(&app.world).enter(|| {
+            let map =
+                TiledMap::from_tmx(&tmx(r#"<data encoding="csv">1,2,1,0,4,1</data>"#)).unwrap();
+            assert!(matches!(
+                map.level::<TiledTestRegistry>(&map.layers[0], &TiledImport::default()),
+                Err(TiledError::UnmappedTile {
+                    x: 0,
+                    y: 1,
+                    gid: 0,
+                    ..
+                })
+            ));
+            let import = TiledImport {
+                empty: Some("tiled_void".to_owned()),
+                ..Default::default()
+            };
+            assert!(matches!(
+                map.level::<TiledTestRegistry>(&map.layers[0], &import),
+                Err(TiledError::UnmappedTile {
+                    x: 1,
+                    y: 1,
+                    gid: 4,
+                    ..
+                })
+            ));
+            let import = TiledImport {
+                property: "kind".to_owned(),
+                ..Default::default()
+            };
+            assert!(matches!(
+                map.level::<TiledTestRegistry>(&map.layers[0], &import),
+                Err(TiledError::UnmappedTile {
+                    x: 0,
+                    y: 0,
+                    gid: 1,
+                    ..
+                })
+            ));
+        });
+    }
+
+    #[test]
+    fn tile_count_mismatch() {
+        assert!(matches!(
+            TiledMap::from_tmx(&tmx(r#"<data encoding="csv">1,2,1,2</data>"#)),
+            Err(TiledError::Invalid(_))
+        ));
+        let data = format!(
+            r#"<data encoding="base64">{}</data>"#,
+            base64(&[1; 7], None)
+        );
+        assert!(matches!(
+            TiledMap::from_tmx(&tmx(&data)),
+            Err(TiledError::Invalid(_))
+        ));
+        assert!(matches!(
+            TiledMap::from_json(
+                r#"{ "width": 65536, "height": 65537, "tilewidth": 16, "tileheight": 16,
+                    "layers": [{ "type": "tilelayer", "name": "huge", "width": 65536, "height": 65537, "data": [1] }] }"#
+            ),
+            Err(TiledError::Invalid(_))
+        ));
+    }
+
+    #[test]
+    fn json() {
+        let app = app();
+        RegistryScope::of(&app.world).enter(|| {
+            let mut map = TiledMap::from_json(&format!(
+                r#"{{
+                    "width": 3, "height": 2, "tilewidth": 16, "tileheight": 16, "infinite": false,
+                    "tilesets": [
+                        {{ "firstgid": 1, "name": "dungeon", "tilewidth": 16, "tileheight": 16, "tiles": [
+                            {{ "id": 0, "properties": [{{ "name": "name", "type": "string", "value": "tiled_wall" }}] }},
+                            {{ "id": 1, "properties": [{{ "name": "name", "type": "string", "value": "tiled_floor" }}] }}
+                        ] }},
+                        {{ "firstgid": 3, "source": "water.tsj" }}
+                    ],
+                    "layers": [
+                        {{ "type": "tilelayer", "name": "ground", "width": 3, "height": 2, "data": [1, 2, 2147483649, 2, 3, 536870914] }},
+                        {{ "type": "tilelayer", "name": "zlib", "width": 3, "height": 2, "encoding": "base64", "compression": "zlib", "data": "{}" }},
+                        {{ "type": "objectgroup", "name": "objects" }}
+                    ]
+                }}"#,
+                base64(&GIDS, Some("zlib"))
+            ))
+            .unwrap();
+            assert_eq!(map.layers.len(), 2);
+            assert_eq!(map.layers[0].tiles, GIDS);
+            assert_eq!(map.layers[1].tiles, GIDS);
+            assert_eq!(map.tilesets[1].source, Some(PathBuf::from("water.tsj")));
+
+            let mut water = TiledTileset::from_json(
+                r#"{ "name": "water", "tilewidth": 16, "tileheight": 16, "tiles": [
+                    { "id": 0, "properties": [{ "name": "name", "type": "string", "value": "tiled_water" }] }
+                ] }"#,
+            )
+            .unwrap();
+            water.first_gid = 3;
+            map.tilesets[1] = water;
+            let level = map
+                .level::<TiledTestRegistry>(&map.layers[0], &TiledImport::default())
+                .unwrap();
+            assert!(level.tiles == expected());
+        });
+    }
+
+    #[test]
+    fn tsx() {
+        let tileset = TiledTileset::from_tsx(
+            r#"<?xml version="1.0" encoding="UTF-8"?>
+            <tileset version="1.10" name="water" tilewidth="16" tileheight="8" tilecount="2" columns="2">
+                <image source="water.png" width="32" height="8"/>
+                <tile id="1"><properties><property name="name" value="tiled_water"/></properties></tile>
+            </tileset>"#,
+        )
+        .unwrap();
+        assert_eq!(tileset.first_gid, 1);
+        assert_eq!((tileset.tile_width, tileset.tile_height), (16, 8));
+        assert_eq!(tileset.image, Some(PathBuf::from("water.png")));
+        assert_eq!(tileset.tiles[&1]["name"], "tiled_water");
+    }
+
+    #[test]
+    fn loader() {
+        let dir = Dir::default();
+        // The water tileset becomes external, its inline tiles are kept in an unused tileset
+        dir.insert_asset_text(
+            Path::new("maps/dungeon.tmx"),
+            &tmx(r#"<data encoding="csv">1,2,1,2,3,2</data>"#).replace(
+                r#"<tileset firstgid="3" name="water" tilewidth="16" tileheight="16">"#,
+                r#"<tileset firstgid="3" source="tilesets/water.tsx"/><tileset firstgid="5" name="unused" tilewidth="16" tileheight="16">"#,
+            ),
+        );
+        dir.insert_asset_text(
+            Path::new("maps/tilesets/water.tsx"),
+            r#"<?xml version="1.0" encoding="UTF-8"?>
+            <tileset version="1.10" name="water" tilewidth="16" tileheight="16">
+                <image source="water.png" width="32" height="16"/>
+                <tile id="0"><properties><property name="name" value="tiled_water"/></properties></tile>
+            </tileset>"#,
+        );
+        let reader = MemoryAssetReader { root: dir };
+
+        let mut app = app();
+        app.register_asset_source(
+            AssetSourceId::Default,
+            AssetSource::build().with_reader(move || Box::new(reader.clone())),
+        )
+        .add_plugins((
+            TaskPoolPlugin::default(),
+            AssetPlugin::default(),
+            TiledPlugin,
+        ));
+
+        let handle = app
+            .world
+            .resource::<AssetServer>()
+            .load::<TiledMap>("maps/dungeon.tmx");
+        for _ in 0..1000 {
+            app.update();
+            if app.world.resource::<Assets<TiledMap>>().contains(&handle) {
+                break;
+            }
+            std::thread::sleep(Duration::from_millis(1));
+        }
+
+        let maps = app.world.resource::<Assets<TiledMap>>();
+        let map = maps.get(&handle).unwrap();
+        assert_eq!(
+            map.tilesets[0].image,
+            Some(PathBuf::from("maps/dungeon.png"))
+        );
+        let water = &map.tilesets[1];
+        assert_eq!((water.first_gid, water.name.as_str()), (3, "water"));
+        assert_eq!(water.image, Some(PathBuf::from("maps/tilesets/water.png")));
+        let level = RegistryScope::of(&app.world)
+            .enter(|| map.level::<TiledTestRegistry>(&map.layers[0], &TiledImport::default()))
+            .unwrap();
+        assert!(level.tiles[4] == RegistryId::new::<TiledWater>());
+    }
+}
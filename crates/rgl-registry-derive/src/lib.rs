@@ -0,0 +1,155 @@
+use proc_macro::TokenStream;
+use proc_macro2::TokenStream as TokenStream2;
+use quote::quote;
+use syn::{parse::ParseStream, parse_macro_input, DeriveInput, Error, LitStr, Token, Type};
+
+/// Implements `Registry` and `ChildRegistry` for a fieldless struct.
+///
+/// The type of the id can be set with `#[registry(id = u8)]`, by default it is `u16`
+#[proc_macro_derive(Registry, attributes(registry))]
+pub fn derive_registry(input: TokenStream) -> TokenStream {
+    let input = parse_macro_input!(input as DeriveInput);
+    expand_registry(input)
+        .unwrap_or_else(Error::into_compile_error)
+        .into()
+}
+
+/// Implements `RegistryItem` for a fieldless struct.
+///
+/// The registry must be set with `#[registry(SomeRegistry)]`,
+/// the name of the item can be declared with `#[registry(SomeRegistry, name = "some_name")]`,
+/// it will be registered in the `RegistryNameCell` of the registry.
+/// The parent item of the same registry can be declared with `#[registry(SomeRegistry, parent = SomeItem)]`,
+/// see `RegistryId::is_a`.
+///
+/// The id of the item is assigned by its key, that is made of `module_path!()` and the name of the struct,
+/// so moving or renaming the item changes the ids of the registry
+#[proc_macro_derive(RegistryItem, attributes(registry))]
+pub fn derive_registry_item(input: TokenStream) -> TokenStream {
+    let input = parse_macro_input!(input as DeriveInput);
+    expand_registry_item(input)
+        .unwrap_or_else(Error::into_compile_error)
+        .into()
+}
+
+/// Implements `RegistryTag` for a fieldless struct, the registry must be set with `#[registry(SomeRegistry)]`
+#[proc_macro_derive(RegistryTag, attributes(registry))]
+pub fn derive_registry_tag(input: TokenStream) -> TokenStream {
+    let input = parse_macro_input!(input as DeriveInput);
+    expand_registry_tag(input)
+        .unwrap_or_else(Error::into_compile_error)
+        .into()
+}
+
+fn check_input(input: &DeriveInput) -> syn::Result<()> {
+    if !input.generics.params.is_empty() {
+        return Err(Error::new_spanned(
+            &input.generics,
+            "registry types can not have generics",
+        ));
+    }
+    Ok(())
+}
+
+fn registry_attr(input: &DeriveInput) -> syn::Result<&syn::Attribute> {
+    let mut attrs = input
+        .attrs
+        .iter()
+        .filter(|attr| attr.path().is_ident("registry"));
+    let attr = attrs.next();
+    if let Some(duplicate) = attrs.next() {
+        return Err(Error::new_spanned(
+            duplicate,
+            "registry attribute can be used only once",
+        ));
+    }
+    attr.ok_or_else(|| {
+        Error::new_spanned(
+            &input.ident,
+            "registry attribute is required, e.g. #[registry(SomeRegistry)]",
+        )
+    })
+}
+
+fn expand_registry(input: DeriveInput) -> syn::Result<TokenStream2> {
+    check_input(&input)?;
+    let ident = &input.ident;
+    let mut id: Type = syn::parse_quote!(u16);
+
+    for attr in input
+        .attrs
+        .iter()
+        .filter(|attr| attr.path().is_ident("registry"))
+    {
+        attr.parse_nested_meta(|meta| {
+            if meta.path.is_ident("id") {
+                id = meta.value()?.parse()?;
+                Ok(())
+            } else {
+                Err(meta.error("unknown registry attribute, expected `id`"))
+            }
+        })?;
+    }
+
+    Ok(quote! {
+        ::rgl_registry::__registry_impl!(#ident, #id);
+        ::rgl_registry::__child_registry_impl!(#ident);
+    })
+}
+
+fn expand_registry_item(input: DeriveInput) -> syn::Result<TokenStream2> {
+    check_input(&input)?;
+    let ident = &input.ident;
+
+    let (registry, name, parent) =
+        registry_attr(&input)?.parse_args_with(|input: ParseStream| {
+            let registry: Type = input.parse()?;
+            let mut name = None;
+            let mut parent = None;
+            while input.parse::<Option<Token![,]>>()?.is_some() && !input.is_empty() {
+                let key: syn::Ident = input.parse()?;
+                input.parse::<Token![=]>()?;
+                if key == "name" {
+                    name = Some(input.parse::<LitStr>()?);
+                } else if key == "parent" {
+                    parent = Some(input.parse::<Type>()?);
+                } else {
+                    return Err(Error::new_spanned(
+                        key,
+                        "unknown registry attribute, expected `name` or `parent`",
+                    ));
+                }
+            }
+            Ok((registry, name, parent))
+        })?;
+
+    let name = name.map(|name| {
+        quote! {
+            ::rgl_registry::__registry_item_name!(#ident, #registry, #name);
+        }
+    });
+
+    let parent = parent.map(|parent| {
+        quote! {
+            ::rgl_registry::__registry_item_parent!(#ident, #parent);
+        }
+    });
+
+    Ok(quote! {
+        ::rgl_registry::__registry_item_impl!(#ident, #registry);
+        #name
+        #parent
+    })
+}
+
+fn expand_registry_tag(input: DeriveInput) -> syn::Result<TokenStream2> {
+    check_input(&input)?;
+    let ident = &input.ident;
+    let registry: Type = registry_attr(&input)?.parse_args()?;
+
+    Ok(quote! {
+        impl ::rgl_registry::RegistryTag for #ident {
+            type Registry = #registry;
+        }
+    })
+}
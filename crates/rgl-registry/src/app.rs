@@ -1,13 +1,26 @@
-use std::{any::TypeId, hash::Hash};
+use std::{
+    any::{type_name, TypeId},
+    hash::Hash,
+    panic::Location,
+    path::Path,
+};
 
 use bevy::{
+    asset::io::AssetSourceId,
+    ecs::schedule::ScheduleLabel,
     prelude::*,
     utils::{HashMap, HashSet},
 };
 
 use crate::{
-    Registry, RegistryDataCell, RegistryId, RegistryIdMap, RegistryItem, RegistryMapConvert,
-    RegistryMapInsert,
+    change::send_changes_system,
+    validation::{check_validation, RegistryValidationPanic},
+    ChangableRegistryManyToManyDataCell, ChangableRegistryOneSidedDataCell,
+    ChangableRegistryTwoSidedDataCellId2Value, Registry, RegistryCellInfo, RegistryDataCell,
+    RegistryDataChanged, RegistryHashMultiMap, RegistryId, RegistryIdMap, RegistryIdMultiMap,
+    RegistryItem, RegistryItemName, RegistryItemSet, RegistryItemsFile, RegistryMapChanges,
+    RegistryMapConvert, RegistryMapInsert, RegistryMapRemove, RegistryNameCollision,
+    RegistryNameSource, RegistryScope, RegistryTag, RegistryTagSet, RegistryValidation,
 };
 
 pub trait RegistryAppExt {
@@ -24,21 +37,81 @@ pub trait RegistryAppExt {
         V: Sync + Send + 'static,
         V: Eq;
 
+    /// Registers the name of the item in the [`RegistryNameCell`](crate::RegistryNameCell),
+    /// the names are used to serialize [`RegistryId`] and can be namespaced, see [`RegistryName`](crate::RegistryName).
+    /// Panics if another item of the registry already has the name
+    fn register_name<I>(&mut self, name: &'static str) -> &mut Self
+    where
+        I: RegistryItem;
+
     fn register_one_sided_data<I, V>(&mut self, value: V) -> &mut Self
     where
         I: RegistryItem,
         V: Sync + Send + 'static;
 
+    /// Adds the value to the [`Vec`] of the item in [`RegistryOneSidedDataCell`](crate::RegistryOneSidedDataCell) with `Vec<V>`,
+    /// the items without values have an empty [`Vec`]
     fn register_one_sided_vec_data<I, V>(&mut self, value: V) -> &mut Self
     where
         I: RegistryItem,
         V: Sync + Send + 'static;
 
+    /// Adds the value to the values of the item in the [`RegistryManyToManyDataCell`](crate::RegistryManyToManyDataCell),
+    /// the same value can be added to many items
+    fn register_many_to_many_data<I, V>(&mut self, value: V) -> &mut Self
+    where
+        I: RegistryItem,
+        V: Hash,
+        V: Clone,
+        V: Sync + Send + 'static,
+        V: Eq;
+
+    /// Sets the value of the one sided data cell for items, that were not registered
+    /// with [`RegistryAppExt::register_one_sided_data`], so the cell is converted even if some items are missing
+    fn register_one_sided_data_default<R, V>(&mut self, default: V) -> &mut Self
+    where
+        R: Registry,
+        V: Clone,
+        V: Sync + Send + 'static;
+
+    /// The same as [`RegistryAppExt::register_one_sided_data_default`] with [`Default::default`]
+    fn init_one_sided_data_default<R, V>(&mut self) -> &mut Self
+    where
+        R: Registry,
+        V: Default,
+        V: Sync + Send + 'static;
+
+    /// Items without a value in the one sided data cell get the value of their nearest ancestor,
+    /// that has one, see [`RegistryAppExt::register_item_parent`].
+    /// The default value is used only if no ancestor has a value
+    fn inherit_one_sided_data<R, V>(&mut self) -> &mut Self
+    where
+        R: Registry,
+        V: Clone,
+        V: Sync + Send + 'static;
+
+    /// Makes the item a child of the parent, so [`RegistryId::is_a`] returns true for them
+    /// and the child inherits the values of the parent, see [`RegistryAppExt::inherit_one_sided_data`].
+    /// Parents are owned by the app, see [`RegistryScope`].
+    /// The parent can also be declared with `#[registry(SomeRegistry, parent = SomeItem)]`
+    fn register_item_parent<I, P>(&mut self) -> &mut Self
+    where
+        I: RegistryItem,
+        P: RegistryItem<Registry = I::Registry>;
+
+    /// The same as [`RegistryAppExt::register_item_parent`], but for items defined at runtime
+    fn register_parent<R>(&mut self, id: RegistryId<R>, parent: RegistryId<R>) -> &mut Self
+    where
+        R: Registry;
+
+    /// Keeps the data cell changable after the Startup phase,
+    /// the changes of its entries are sent as [`RegistryDataChanged`] events
     fn keep_changable_two_sided_data_id2id<R1, R2>(&mut self) -> &mut Self
     where
         R1: Registry,
         R2: Registry;
 
+    /// See [`RegistryAppExt::keep_changable_two_sided_data_id2id`]
     fn keep_changable_two_sided_data_id2value<R, V>(&mut self) -> &mut Self
     where
         R: Registry,
@@ -47,50 +120,154 @@ pub trait RegistryAppExt {
         V: Sync + Send + 'static,
         V: Eq;
 
+    /// See [`RegistryAppExt::keep_changable_two_sided_data_id2id`]
     fn keep_changable_one_sided_data<R, V>(&mut self) -> &mut Self
     where
         R: Registry,
         V: Sync + Send + 'static;
+
+    /// Keeps the many to many data cell changable after the Startup phase,
+    /// the changes of the values of its ids are sent as [`RegistryDataChanged`] events
+    fn keep_changable_many_to_many_data<R, V>(&mut self) -> &mut Self
+    where
+        R: Registry,
+        V: Hash,
+        V: Clone,
+        V: Sync + Send + 'static,
+        V: Eq;
+
+    /// Adds the item to the tag, see [`RegistryTagSet`]
+    fn add_to_tag<T, I>(&mut self) -> &mut Self
+    where
+        T: RegistryTag,
+        I: RegistryItem<Registry = T::Registry>;
+
+    /// The same as [`RegistryAppExt::add_to_tag`], but for items defined at runtime
+    fn add_id_to_tag<T>(&mut self, id: RegistryId<T::Registry>) -> &mut Self
+    where
+        T: RegistryTag;
+
+    /// Adds the item to the [`RegistryItemSet`] of the app without registering any data for it
+    fn add_registry_item<I>(&mut self) -> &mut Self
+    where
+        I: RegistryItem;
+
+    /// Makes the registry local to the app: only the items of its [`RegistryItemSet`]
+    /// are validated and stored in the converted data cells, so apps in one process
+    /// can use different items of the same registry. The names, dynamic items and parents
+    /// are owned by every app even if the registry isn't local, see [`RegistryScope`].
+    /// Must be called before the Startup phase
+    fn local_registry<R>(&mut self) -> &mut Self
+    where
+        R: Registry;
+
+    /// Panics if any data cell has missing entries instead of logging them, see [`RegistryValidation`]
+    fn panic_on_invalid_registries(&mut self) -> &mut Self;
+
+    /// Registers the names, that were declared with `#[registry(SomeRegistry, name = "some_name")]`,
+    /// in the [`RegistryNameCell`](crate::RegistryNameCell). It is done automatically,
+    /// if any other name of the registry is registered
+    fn register_declared_names<R>(&mut self) -> &mut Self
+    where
+        R: Registry;
+
+    /// Reserves a new item of the registry at runtime and registers its name in the
+    /// [`RegistryNameCell`](crate::RegistryNameCell). The id of the item can be found using
+    /// [`RegistryId::from_name`]. Must be called before the data cells are converted in [`PreStartup`].
+    /// The item is defined only in the [`RegistryScope`] of the app
+    fn register_dynamic_item<R>(&mut self, name: impl Into<String>) -> &mut Self
+    where
+        R: Registry;
+
+    /// Registers every item of the [`RegistryItemsFile`] with the given path in the default asset source,
+    /// see [`RegistryAppExt::register_dynamic_item`]. The file is read while the app is built,
+    /// so [`AssetPlugin`] must be added before
+    fn load_dynamic_items<R>(&mut self, path: impl AsRef<Path>) -> &mut Self
+    where
+        R: Registry;
+}
+
+/// The phase of the registries of the app.
+/// The data can be registered only in [`RegistryPhase::Registering`], that lasts until [`RegistrySet`] in [`PreStartup`]
+#[derive(Resource, Clone, Copy, Debug, Default, PartialEq, Eq)]
+pub enum RegistryPhase {
+    /// The app is being built, the registry data can be registered
+    #[default]
+    Registering,
+    /// The data cells were converted, nothing can be registered anymore
+    Frozen,
 }
 
+/// The set in [`PreStartup`], where the data cells are converted and the registries are frozen.
+/// Systems reading the converted data cells in [`PreStartup`] must run after it
+#[derive(SystemSet, Clone, Copy, Debug, PartialEq, Eq, Hash)]
+pub struct RegistrySet;
+
+/// Sent once, when the registries are frozen, see [`RegistryPhase`]
+#[derive(Event, Clone, Copy, Debug)]
+pub struct RegistryFrozen;
+
+type Converter = fn(&mut World);
+
 #[derive(Resource, Default)]
 struct RegistrySystemsData {
     added_systems: HashSet<TypeId>,
-    cancelled_systems: HashSet<TypeId>,
+    /// The functions converting the data cells and the types of the cells
+    converters: Vec<(TypeId, Converter)>,
+    cancelled_converters: HashSet<TypeId>,
+    /// The data cells and the types of their keys, see [`RegistryDump`](crate::RegistryDump)
+    cells: Vec<(TypeId, RegistryCellInfo)>,
 }
 
 impl RegistrySystemsData {
-    pub fn is_added<T: 'static>(&self, _marker: &T) -> bool {
-        self.added_systems.contains(&TypeId::of::<T>())
-    }
-
     pub fn add_if_not_added<T: IntoSystemConfigs<M> + 'static, M>(
         app: &mut App,
         func: T,
-        pre: bool,
+        schedule: impl ScheduleLabel,
     ) {
-        let mut acs = app.world.remove_resource::<RegistrySystemsData>().unwrap();
-        if !acs.is_added(&func) {
-            if pre {
-                app.add_systems(PreStartup, func);
-            } else {
-                app.add_systems(Startup, func);
-            }
-            acs.added_systems.insert(TypeId::of::<T>());
+        let added = app
+            .world
+            .resource_mut::<RegistrySystemsData>()
+            .added_systems
+            .insert(TypeId::of::<T>());
+        if added {
+            app.add_systems(schedule, func);
         }
-        app.insert_resource(acs);
     }
 
-    pub fn cancel<T: 'static>(&mut self, _marker: T) {
-        self.cancelled_systems.insert(TypeId::of::<T>());
+    pub fn add_converter<I, V, C1, C2>(app: &mut App)
+    where
+        I: Sync + Send + 'static,
+        V: Sync + Send + 'static,
+        C1: Sync + Send + 'static,
+        C2: Sync + Send + 'static,
+        C1::Converted: Sync + Send + 'static,
+        C2::Converted: Sync + Send + 'static,
+        C1: RegistryMapConvert,
+        C2: RegistryMapConvert,
+    {
+        let cell = TypeId::of::<RegistryDataCell<I, V, C1, C2>>();
+        let mut acs = app.world.resource_mut::<RegistrySystemsData>();
+        if !acs.converters.iter().any(|(added, _)| *added == cell) {
+            acs.converters.push((cell, convert::<I, V, C1, C2>));
+            acs.cells.push((
+                TypeId::of::<I>(),
+                RegistryCellInfo {
+                    value: type_name::<V>(),
+                    cell: type_name::<RegistryDataCell<I, V, C1, C2>>(),
+                    two_sided: TypeId::of::<C2>() != TypeId::of::<()>(),
+                },
+            ));
+        }
     }
 
-    pub fn is_cancelled<T: 'static>(&self, _marker: T) -> bool {
-        self.cancelled_systems.contains(&TypeId::of::<T>())
+    /// The data cell of the given type will not be converted
+    pub fn cancel<Cell: 'static>(&mut self) {
+        self.cancelled_converters.insert(TypeId::of::<Cell>());
     }
 }
 
-fn convert_system<I, V, C1, C2>(mut commands: Commands)
+fn convert<I, V, C1, C2>(world: &mut World)
 where
     I: Sync + Send + 'static,
     V: Sync + Send + 'static,
@@ -101,28 +278,72 @@ where
     C1: RegistryMapConvert,
     C2: RegistryMapConvert,
 {
-    commands.add(|world: &mut World| {
-        if !world
-            .resource::<RegistrySystemsData>()
-            .is_cancelled(convert_system::<I, V, C1, C2>)
-        {
-            if let Some(data_cell) = world.remove_resource::<RegistryDataCell<I, V, C1, C2>>() {
-                world.insert_resource(data_cell.convert());
-            }
+    if let Some(mut data_cell) = world.remove_resource::<RegistryDataCell<I, V, C1, C2>>() {
+        data_cell.restrict(world);
+        let missing = data_cell.missing();
+        if missing.is_empty() {
+            world.insert_resource(data_cell.convert());
+        } else {
+            // The cell is dropped, so it can't be mistaken for a converted one
+            let mut validation = world.resource_mut::<RegistryValidation>();
+            validation.missing.extend(missing);
+            validation.skipped.push(type_name::<
+                RegistryDataCell<I, V, C1::Converted, C2::Converted>,
+            >());
         }
-    });
+    }
 }
 
-fn remove_added_convert_systems_res(mut commands: Commands) {
-    commands.remove_resource::<RegistrySystemsData>();
+pub(crate) fn registered_cells(world: &World) -> Vec<(TypeId, RegistryCellInfo)> {
+    world
+        .get_resource::<RegistrySystemsData>()
+        .map(|acs| acs.cells.clone())
+        .unwrap_or_default()
+}
+
+fn freeze_system(world: &mut World) {
+    let acs = world.resource::<RegistrySystemsData>();
+    let converters = acs
+        .converters
+        .iter()
+        .filter(|(cell, _)| !acs.cancelled_converters.contains(cell))
+        .map(|(_, convert)| *convert)
+        .collect::<Vec<_>>();
+    // Converters look up the ids of the app, so they run inside of its scope
+    let scope = RegistryScope::of(world);
+    scope.enter(|| {
+        for convert in converters {
+            convert(world);
+        }
+    });
+    scope.seal();
+    check_validation(world);
+    *world.resource_mut::<RegistryPhase>() = RegistryPhase::Frozen;
+    world
+        .resource_mut::<Events<RegistryFrozen>>()
+        .send(RegistryFrozen);
 }
 
 fn rsd_init(app: &mut App) {
-    app.world.init_resource::<RegistrySystemsData>();
-    RegistrySystemsData::add_if_not_added(app, remove_added_convert_systems_res, false);
+    if !app.world.contains_resource::<RegistrySystemsData>() {
+        app.init_resource::<RegistrySystemsData>()
+            .init_resource::<RegistryScope>()
+            .init_resource::<RegistryValidation>()
+            .init_resource::<RegistryPhase>()
+            .add_event::<RegistryFrozen>()
+            .add_systems(PreStartup, freeze_system.in_set(RegistrySet));
+    }
+    if *app.world.resource::<RegistryPhase>() == RegistryPhase::Frozen {
+        panic!(
+            "Registry data can not be registered after the registries were frozen in {:?}, \
+            it must be registered while the app is built (Plugin::build or Plugin::finish)",
+            RegistrySet
+        );
+    }
 }
 
-fn insert<I, V, C1, C2>(app: &mut App, id: I, value: V)
+/// Inserts the entry, returns the id, that had the value before
+fn insert<I, V, C1, C2>(app: &mut App, id: I, value: V) -> Option<I>
 where
     I: Sync + Send + 'static,
     V: Sync + Send + 'static,
@@ -130,22 +351,36 @@ where
     C2: Sync + Send + 'static,
     C1::Converted: Sync + Send + 'static,
     C2::Converted: Sync + Send + 'static,
-    I: Clone,
-    V: Clone,
+    I: Clone + PartialEq,
+    V: Clone + PartialEq,
     C1: Default,
     C2: Default,
     C1: RegistryMapConvert,
     C2: RegistryMapConvert,
-    C1: RegistryMapInsert<I, V>,
-    C2: RegistryMapInsert<V, I>,
+    C1: RegistryMapInsert<I, V> + RegistryMapRemove<I, V>,
+    C2: RegistryMapInsert<V, I> + RegistryMapRemove<V, I>,
 {
-    RegistrySystemsData::add_if_not_added(app, convert_system::<I, V, C1, C2>, true);
+    RegistrySystemsData::add_converter::<I, V, C1, C2>(app);
     app.init_resource::<RegistryDataCell<I, V, C1, C2>>();
-    // TODO: Debug?
-    let _ = app
-        .world
+    app.world
         .resource_mut::<RegistryDataCell<I, V, C1, C2>>()
-        .insert(id, value);
+        .insert(id, value)
+        .1
+}
+
+/// Panics if the value of the two sided data cell was registered for another item,
+/// the value would point only at the last one
+#[track_caller]
+fn check_displaced<R: Registry, V>(id: RegistryId<R>, displaced: Option<RegistryId<R>>) {
+    if let Some(other) = displaced {
+        panic!(
+            "Value of two sided data {} is registered for both {:?} and {:?} in registry {}",
+            type_name::<V>(),
+            other,
+            id,
+            type_name::<R>()
+        );
+    }
 }
 
 fn insert_one_sided<I, V, C1>(app: &mut App, id: I, value: V)
@@ -158,7 +393,7 @@ where
     C1: RegistryMapConvert,
     C1: RegistryMapInsert<I, V>,
 {
-    RegistrySystemsData::add_if_not_added(app, convert_system::<I, V, C1, ()>, true);
+    RegistrySystemsData::add_converter::<I, V, C1, ()>(app);
     app.init_resource::<RegistryDataCell<I, V, C1, ()>>();
     // TODO: Debug?
     let _ = app
@@ -167,22 +402,127 @@ where
         .insert_one_sided(id, value);
 }
 
+fn set_one_sided_default<R, V>(app: &mut App, default: impl Fn() -> V + Sync + Send + 'static)
+where
+    R: Registry,
+    V: Sync + Send + 'static,
+{
+    rsd_init(app);
+    RegistrySystemsData::add_converter::<RegistryId<R>, V, RegistryIdMap<R, V>, ()>(app);
+    app.init_resource::<ChangableRegistryOneSidedDataCell<R, V>>();
+    app.world
+        .resource_mut::<ChangableRegistryOneSidedDataCell<R, V>>()
+        .c1
+        .set_default(default);
+}
+
+fn add_item<R: Registry>(app: &mut App, id: RegistryId<R>) {
+    app.init_resource::<RegistryItemSet<R>>();
+    app.world.resource_mut::<RegistryItemSet<R>>().insert(id);
+}
+
+fn track_changes<R, V, C1, C2>(app: &mut App)
+where
+    R: Registry,
+    V: Sync + Send + 'static,
+    C1: RegistryMapChanges<R> + Sync + Send + 'static,
+    C2: Sync + Send + 'static,
+{
+    app.add_event::<RegistryDataChanged<R, V>>();
+    RegistrySystemsData::add_if_not_added(app, send_changes_system::<R, V, C1, C2>, Last);
+}
+
+fn mirror_name<R: Registry>(
+    app: &App,
+    id: RegistryId<R>,
+    name: &'static str,
+    location: &'static Location<'static>,
+) {
+    // Names are mirrored to the scope of the app, so ids can be serialized without the world
+    let counter = app.world.resource::<RegistryScope>().counter::<R>();
+    let mut counter = counter.lock();
+    let numeric = id.numeric();
+    if let Err(other) = counter.set_name(numeric, name, Some(location)) {
+        let collision = counter.collision(name, other, numeric, Some(location));
+        drop(counter);
+        panic!("{}", collision);
+    }
+}
+
+fn init_name_cell<R: Registry>(app: &mut App) {
+    if app
+        .world
+        .contains_resource::<ChangableRegistryTwoSidedDataCellId2Value<R, RegistryItemName>>()
+    {
+        return;
+    }
+    RegistrySystemsData::add_converter::<
+        RegistryId<R>,
+        RegistryItemName,
+        RegistryIdMap<R, RegistryItemName>,
+        HashMap<RegistryItemName, RegistryId<R>>,
+    >(app);
+    let mut cell = ChangableRegistryTwoSidedDataCellId2Value::<R, RegistryItemName>::default();
+    // Items without a name can't be serialized, but they are valid
+    cell.c1.set_optional();
+    let declared_names = app
+        .world
+        .resource::<RegistryScope>()
+        .counter::<R>()
+        .lock()
+        .declared_names()
+        .collect::<Vec<_>>();
+    for (id, name) in declared_names {
+        cell.insert(
+            RegistryId::from_numeric_unchecked(id),
+            RegistryItemName::new(name),
+        );
+    }
+    app.insert_resource(cell)
+        .register_type::<RegistryId<R>>()
+        .register_type::<Vec<RegistryId<R>>>();
+}
+
+fn insert_name<R: Registry>(
+    app: &mut App,
+    id: RegistryId<R>,
+    name: &'static str,
+    location: &'static Location<'static>,
+) {
+    init_name_cell::<R>(app);
+    mirror_name(app, id.clone(), name, location);
+    insert::<
+        RegistryId<R>,
+        RegistryItemName,
+        RegistryIdMap<R, RegistryItemName>,
+        HashMap<RegistryItemName, RegistryId<R>>,
+    >(app, id, RegistryItemName::new(name));
+}
+
 impl RegistryAppExt for App {
+    #[track_caller]
     fn register_two_sided_data_id2id<I1, I2>(&mut self) -> &mut Self
     where
         I1: RegistryItem,
         I2: RegistryItem,
     {
         rsd_init(self);
-        insert::<
+        add_item(self, RegistryId::<I1::Registry>::new::<I1>());
+        add_item(self, RegistryId::<I2::Registry>::new::<I2>());
+        let displaced = insert::<
             RegistryId<I1::Registry>,
             RegistryId<I2::Registry>,
             RegistryIdMap<I1::Registry, RegistryId<I2::Registry>>,
             RegistryIdMap<I2::Registry, RegistryId<I1::Registry>>,
         >(self, RegistryId::new::<I1>(), RegistryId::new::<I2>());
+        check_displaced::<I1::Registry, RegistryId<I2::Registry>>(
+            RegistryId::new::<I1>(),
+            displaced,
+        );
         self
     }
 
+    #[track_caller]
     fn register_two_sided_data_id2value<I, V>(&mut self, value: V) -> &mut Self
     where
         I: RegistryItem,
@@ -192,12 +532,25 @@ impl RegistryAppExt for App {
         V: Eq,
     {
         rsd_init(self);
-        insert::<
+        add_item(self, RegistryId::<I::Registry>::new::<I>());
+        let displaced = insert::<
             RegistryId<I::Registry>,
             V,
             RegistryIdMap<I::Registry, V>,
             HashMap<V, RegistryId<I::Registry>>,
         >(self, RegistryId::new::<I>(), value);
+        check_displaced::<I::Registry, V>(RegistryId::new::<I>(), displaced);
+        self
+    }
+
+    #[track_caller]
+    fn register_name<I>(&mut self, name: &'static str) -> &mut Self
+    where
+        I: RegistryItem,
+    {
+        rsd_init(self);
+        add_item(self, RegistryId::<I::Registry>::new::<I>());
+        insert_name(self, RegistryId::new::<I>(), name, Location::caller());
         self
     }
 
@@ -207,6 +560,7 @@ impl RegistryAppExt for App {
         V: Sync + Send + 'static,
     {
         rsd_init(self);
+        add_item(self, RegistryId::<I::Registry>::new::<I>());
         insert_one_sided::<RegistryId<I::Registry>, V, RegistryIdMap<I::Registry, V>>(
             self,
             RegistryId::new::<I>(),
@@ -220,34 +574,116 @@ impl RegistryAppExt for App {
         I: RegistryItem,
         V: Sync + Send + 'static,
     {
-        rsd_init(self);
-        self.init_resource::<RegistryDataCell<RegistryId<I::Registry>, Vec<V>, RegistryIdMap<I::Registry, Vec<V>>>>();
-        RegistrySystemsData::add_if_not_added(
-            self,
-            convert_system::<RegistryId<I::Registry>, V, RegistryIdMap<I::Registry, Vec<V>>, ()>,
-            true,
-        );
+        add_item(self, RegistryId::<I::Registry>::new::<I>());
+        // The items without values have an empty Vec
+        set_one_sided_default::<I::Registry, Vec<V>>(self, Vec::new);
         self.world
-            .resource_mut::<RegistryDataCell<RegistryId<I::Registry>, Vec<V>, RegistryIdMap<I::Registry, Vec<V>>>>()
+            .resource_mut::<ChangableRegistryOneSidedDataCell<I::Registry, Vec<V>>>()
             .value_mut_or_insert_default(&RegistryId::new::<I>())
             .push(value);
         self
     }
 
+    fn register_many_to_many_data<I, V>(&mut self, value: V) -> &mut Self
+    where
+        I: RegistryItem,
+        V: Hash,
+        V: Clone,
+        V: Sync + Send + 'static,
+        V: Eq,
+    {
+        rsd_init(self);
+        add_item(self, RegistryId::<I::Registry>::new::<I>());
+        RegistrySystemsData::add_converter::<
+            RegistryId<I::Registry>,
+            V,
+            RegistryIdMultiMap<I::Registry, V>,
+            RegistryHashMultiMap<V, RegistryId<I::Registry>>,
+        >(self);
+        self.init_resource::<ChangableRegistryManyToManyDataCell<I::Registry, V>>();
+        self.world
+            .resource_mut::<ChangableRegistryManyToManyDataCell<I::Registry, V>>()
+            .insert_pair(RegistryId::new::<I>(), value);
+        self
+    }
+
+    fn register_one_sided_data_default<R, V>(&mut self, default: V) -> &mut Self
+    where
+        R: Registry,
+        V: Clone,
+        V: Sync + Send + 'static,
+    {
+        set_one_sided_default::<R, V>(self, move || default.clone());
+        self
+    }
+
+    fn init_one_sided_data_default<R, V>(&mut self) -> &mut Self
+    where
+        R: Registry,
+        V: Default,
+        V: Sync + Send + 'static,
+    {
+        set_one_sided_default::<R, V>(self, V::default);
+        self
+    }
+
+    fn inherit_one_sided_data<R, V>(&mut self) -> &mut Self
+    where
+        R: Registry,
+        V: Clone,
+        V: Sync + Send + 'static,
+    {
+        rsd_init(self);
+        RegistrySystemsData::add_converter::<RegistryId<R>, V, RegistryIdMap<R, V>, ()>(self);
+        self.init_resource::<ChangableRegistryOneSidedDataCell<R, V>>();
+        self.world
+            .resource_mut::<ChangableRegistryOneSidedDataCell<R, V>>()
+            .c1
+            .set_inherit(V::clone);
+        self
+    }
+
+    fn register_item_parent<I, P>(&mut self) -> &mut Self
+    where
+        I: RegistryItem,
+        P: RegistryItem<Registry = I::Registry>,
+    {
+        self.register_parent(RegistryId::new::<I>(), RegistryId::new::<P>())
+    }
+
+    fn register_parent<R>(&mut self, id: RegistryId<R>, parent: RegistryId<R>) -> &mut Self
+    where
+        R: Registry,
+    {
+        rsd_init(self);
+        self.world
+            .resource::<RegistryScope>()
+            .counter::<R>()
+            .lock()
+            .set_parent(id.numeric(), parent.numeric());
+        self
+    }
+
     fn keep_changable_two_sided_data_id2id<R1, R2>(&mut self) -> &mut Self
     where
         R1: Registry,
         R2: Registry,
     {
         rsd_init(self);
-        self.world.resource_mut::<RegistrySystemsData>().cancel(
-            convert_system::<
+        self.world
+            .resource_mut::<RegistrySystemsData>()
+            .cancel::<RegistryDataCell<
                 RegistryId<R1>,
                 RegistryId<R2>,
                 RegistryIdMap<R1, RegistryId<R2>>,
                 RegistryIdMap<R2, RegistryId<R1>>,
-            >,
-        );
+            >>();
+        track_changes::<
+            R1,
+            RegistryId<R2>,
+            RegistryIdMap<R1, RegistryId<R2>>,
+            RegistryIdMap<R2, RegistryId<R1>>,
+        >(self);
         self
     }
 
@@ -260,9 +696,10 @@ impl RegistryAppExt for App {
         V: Eq,
     {
         rsd_init(self);
-        self.world.resource_mut::<RegistrySystemsData>().cancel(
-            convert_system::<RegistryId<R>, V, RegistryIdMap<R, V>, HashMap<V, RegistryId<R>>>,
-        );
+        self.world
+            .resource_mut::<RegistrySystemsData>()
+            .cancel::<ChangableRegistryTwoSidedDataCellId2Value<R, V>>();
+        track_changes::<R, V, RegistryIdMap<R, V>, HashMap<V, RegistryId<R>>>(self);
         self
     }
 
@@ -274,7 +711,145 @@ impl RegistryAppExt for App {
         rsd_init(self);
         self.world
             .resource_mut::<RegistrySystemsData>()
-            .cancel(convert_system::<RegistryId<R>, V, RegistryIdMap<R, V>, ()>);
+            .cancel::<RegistryDataCell<RegistryId<R>, V, RegistryIdMap<R, V>, ()>>();
+        track_changes::<R, V, RegistryIdMap<R, V>, ()>(self);
+        self
+    }
+
+    fn keep_changable_many_to_many_data<R, V>(&mut self) -> &mut Self
+    where
+        R: Registry,
+        V: Hash,
+        V: Clone,
+        V: Sync + Send + 'static,
+        V: Eq,
+    {
+        rsd_init(self);
+        self.world
+            .resource_mut::<RegistrySystemsData>()
+            .cancel::<RegistryDataCell<
+                RegistryId<R>,
+                V,
+                RegistryIdMultiMap<R, V>,
+                RegistryHashMultiMap<V, RegistryId<R>>,
+            >>();
+        track_changes::<R, V, RegistryIdMultiMap<R, V>, RegistryHashMultiMap<V, RegistryId<R>>>(
+            self,
+        );
+        self
+    }
+
+    fn add_to_tag<T, I>(&mut self) -> &mut Self
+    where
+        T: RegistryTag,
+        I: RegistryItem<Registry = T::Registry>,
+    {
+        self.add_id_to_tag::<T>(RegistryId::new::<I>())
+    }
+
+    fn add_id_to_tag<T>(&mut self, id: RegistryId<T::Registry>) -> &mut Self
+    where
+        T: RegistryTag,
+    {
+        rsd_init(self);
+        self.init_resource::<RegistryTagSet<T>>();
+        self.world.resource_mut::<RegistryTagSet<T>>().insert(id);
+        self
+    }
+
+    fn add_registry_item<I>(&mut self) -> &mut Self
+    where
+        I: RegistryItem,
+    {
+        rsd_init(self);
+        add_item(self, RegistryId::<I::Registry>::new::<I>());
+        self
+    }
+
+    fn local_registry<R>(&mut self) -> &mut Self
+    where
+        R: Registry,
+    {
+        rsd_init(self);
+        self.init_resource::<RegistryItemSet<R>>();
+        self.world.resource_mut::<RegistryItemSet<R>>().set_local();
+        self
+    }
+
+    fn panic_on_invalid_registries(&mut self) -> &mut Self {
+        rsd_init(self);
+        self.init_resource::<RegistryValidationPanic>();
+        self
+    }
+
+    fn register_declared_names<R>(&mut self) -> &mut Self
+    where
+        R: Registry,
+    {
+        rsd_init(self);
+        init_name_cell::<R>(self);
+        self
+    }
+
+    #[track_caller]
+    fn register_dynamic_item<R>(&mut self, name: impl Into<String>) -> &mut Self
+    where
+        R: Registry,
+    {
+        rsd_init(self);
+        let location = Location::caller();
+        let name: &'static str = Box::leak(name.into().into_boxed_str());
+        self.init_resource::<RegistryItemSet<R>>();
+        // The item is only defined in the scope of this app
+        let scope = self.world.resource::<RegistryScope>().clone();
+        let counter = scope.counter::<R>();
+        let other = counter.lock().name_id(name);
+        if let Some(other) = other {
+            let mut counter = counter.lock();
+            let collision = RegistryNameCollision {
+                name,
+                registry: counter.registry(),
+                first: counter.name_source(other),
+                second: RegistryNameSource {
+                    key: Some(name),
+                    location: Some(location),
+                },
+            };
+            drop(counter);
+            panic!("{}", collision);
+        }
+        let id = scope.enter(|| RegistryId::<R>::new_dynamic(name));
+        add_item(self, id.clone());
+        insert_name(self, id, name, location);
+        self
+    }
+
+    #[track_caller]
+    fn load_dynamic_items<R>(&mut self, path: impl AsRef<Path>) -> &mut Self
+    where
+        R: Registry,
+    {
+        let server = self.world.get_resource::<AssetServer>().unwrap_or_else(|| {
+            panic!(
+                "AssetPlugin must be added before loading items of registry {}",
+                type_name::<R>()
+            )
+        });
+        let source = server
+            .get_source(AssetSourceId::Default)
+            .unwrap_or_else(|err| panic!("{}", err));
+        let read = RegistryItemsFile::read(source.reader(), path.as_ref());
+        let file = bevy::tasks::block_on(read).unwrap_or_else(|err| {
+            panic!(
+                "Couldn't load items of registry {} from {}: {}",
+                type_name::<R>(),
+                path.as_ref().display(),
+                err
+            )
+        });
+        for name in file.items {
+            self.register_dynamic_item::<R>(name);
+        }
         self
     }
 }
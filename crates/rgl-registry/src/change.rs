@@ -0,0 +1,94 @@
+use std::{collections::BTreeMap, marker::PhantomData};
+
+use bevy::prelude::*;
+
+use crate::{Registry, RegistryDataCell, RegistryId, RegistryIdNumeric};
+
+#[derive(Clone, Copy, Debug, PartialEq, Eq, Hash)]
+pub enum RegistryDataChangeKind {
+    /// The item had no value before
+    Inserted,
+    /// The value of the item was replaced or mutably borrowed
+    Overwritten,
+    /// The item lost its value, e.g. another item of a two sided cell took it
+    Removed,
+}
+
+/// Sent in [`Last`] for every entry of a changable data cell, that was inserted, overwritten or removed this frame.
+/// The cell must be kept changable with one of the `keep_changable_*` methods of
+/// [`RegistryAppExt`](crate::RegistryAppExt). The entries registered before the first frame
+/// are sent as [`RegistryDataChangeKind::Inserted`] in the first frame
+#[derive(Event)]
+pub struct RegistryDataChanged<R: Registry, V> {
+    pub id: RegistryId<R>,
+    pub kind: RegistryDataChangeKind,
+    _marker: PhantomData<fn() -> V>,
+}
+
+impl<R: Registry, V> RegistryDataChanged<R, V> {
+    pub fn new(id: RegistryId<R>, kind: RegistryDataChangeKind) -> Self {
+        Self {
+            id,
+            kind,
+            _marker: PhantomData,
+        }
+    }
+}
+
+impl<R: Registry, V> Clone for RegistryDataChanged<R, V> {
+    fn clone(&self) -> Self {
+        Self::new(self.id.clone(), self.kind)
+    }
+}
+
+/// Map, that keeps track of its changed entries, see [`RegistryDataChanged`]
+pub trait RegistryMapChanges<R: Registry> {
+    /// Returns the entries, that were changed since the last call, and forgets them
+    fn take_changes(&mut self) -> Vec<(RegistryId<R>, RegistryDataChangeKind)>;
+}
+
+/// Merges the change with the earlier change of the entry in this frame,
+/// so an inserted and then overwritten entry is still inserted
+/// and an inserted and then removed entry is not changed at all
+pub(crate) fn record_change(
+    changes: &mut BTreeMap<RegistryIdNumeric, RegistryDataChangeKind>,
+    id: RegistryIdNumeric,
+    kind: RegistryDataChangeKind,
+) {
+    use RegistryDataChangeKind::*;
+
+    match (changes.get(&id), kind) {
+        (Some(Inserted), Removed) => {
+            changes.remove(&id);
+        }
+        (Some(Removed), Inserted) => {
+            changes.insert(id, Overwritten);
+        }
+        (Some(_), Removed) | (None, _) => {
+            changes.insert(id, kind);
+        }
+        (Some(_), _) => {}
+    }
+}
+
+type ChangableCell<R, V, C1, C2> = RegistryDataCell<RegistryId<R>, V, C1, C2>;
+
+pub(crate) fn send_changes_system<R, V, C1, C2>(
+    cell: Option<ResMut<ChangableCell<R, V, C1, C2>>>,
+    mut events: EventWriter<RegistryDataChanged<R, V>>,
+) where
+    R: Registry,
+    V: Sync + Send + 'static,
+    C1: RegistryMapChanges<R> + Sync + Send + 'static,
+    C2: Sync + Send + 'static,
+{
+    if let Some(mut cell) = cell {
+        // Taking the changes is not a change of the cell
+        let changes = cell.bypass_change_detection().c1.take_changes();
+        events.send_batch(
+            changes
+                .into_iter()
+                .map(|(id, kind)| RegistryDataChanged::new(id, kind)),
+        );
+    }
+}
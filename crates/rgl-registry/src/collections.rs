@@ -0,0 +1,402 @@
+use std::{
+    fmt,
+    marker::PhantomData,
+    ops::{Index, IndexMut},
+};
+
+use crate::{
+    ConvertedRegistryIdMap, Registry, RegistryId, RegistryIdFilter, RegistryIdMap,
+    RegistryIdNumeric, RegistryMapConvert, RegistryMissingEntry, RegistryValidationError,
+};
+
+const BLOCK_BITS: usize = u64::BITS as usize;
+
+/// A set of registry ids stored as a bitset, one bit per item of the registry
+pub struct RegistryIdSet<R: Registry> {
+    blocks: Vec<u64>,
+    _marker: PhantomData<R>,
+}
+
+impl<R: Registry> RegistryIdSet<R> {
+    /// Creates an empty set with the blocks for every item of the registry
+    pub fn new() -> Self {
+        Self {
+            blocks: vec![0; R::count().div_ceil(BLOCK_BITS)],
+            _marker: PhantomData,
+        }
+    }
+
+    /// Creates a set with every item of the registry
+    pub fn full() -> Self {
+        RegistryId::iter_all().collect()
+    }
+
+    /// Adds the id to the set, returns true if it wasn't in the set
+    pub fn insert(&mut self, id: RegistryId<R>) -> bool {
+        let (block, bit) = Self::position(id);
+        if block >= self.blocks.len() {
+            self.blocks.resize(block + 1, 0);
+        }
+        let inserted = self.blocks[block] & bit == 0;
+        self.blocks[block] |= bit;
+        inserted
+    }
+
+    /// Removes the id from the set, returns true if it was in the set
+    pub fn remove(&mut self, id: &RegistryId<R>) -> bool {
+        let (block, bit) = Self::position(id.clone());
+        match self.blocks.get_mut(block) {
+            Some(value) if *value & bit != 0 => {
+                *value &= !bit;
+                true
+            }
+            _ => false,
+        }
+    }
+
+    pub fn contains(&self, id: &RegistryId<R>) -> bool {
+        let (block, bit) = Self::position(id.clone());
+        matches!(self.blocks.get(block), Some(value) if value & bit != 0)
+    }
+
+    pub fn len(&self) -> usize {
+        self.blocks
+            .iter()
+            .map(|block| block.count_ones() as usize)
+            .sum()
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.blocks.iter().all(|block| *block == 0)
+    }
+
+    pub fn clear(&mut self) {
+        self.blocks.fill(0);
+    }
+
+    /// Iterates over the ids in the ascending order
+    pub fn iter(&self) -> impl Iterator<Item = RegistryId<R>> + '_ {
+        self.blocks.iter().enumerate().flat_map(|(i, &block)| {
+            (0..BLOCK_BITS)
+                .filter(move |bit| block & (1 << bit) != 0)
+                .map(move |bit| {
+                    RegistryId::from_numeric_unchecked((i * BLOCK_BITS + bit) as RegistryIdNumeric)
+                })
+        })
+    }
+
+    pub fn union_with(&mut self, other: &Self) {
+        if other.blocks.len() > self.blocks.len() {
+            self.blocks.resize(other.blocks.len(), 0);
+        }
+        for (block, other) in self.blocks.iter_mut().zip(other.blocks.iter()) {
+            *block |= other;
+        }
+    }
+
+    pub fn intersect_with(&mut self, other: &Self) {
+        self.blocks.truncate(other.blocks.len());
+        for (block, other) in self.blocks.iter_mut().zip(other.blocks.iter()) {
+            *block &= other;
+        }
+    }
+
+    pub fn difference_with(&mut self, other: &Self) {
+        for (block, other) in self.blocks.iter_mut().zip(other.blocks.iter()) {
+            *block &= !other;
+        }
+    }
+
+    pub fn symmetric_difference_with(&mut self, other: &Self) {
+        if other.blocks.len() > self.blocks.len() {
+            self.blocks.resize(other.blocks.len(), 0);
+        }
+        for (block, other) in self.blocks.iter_mut().zip(other.blocks.iter()) {
+            *block ^= other;
+        }
+    }
+
+    pub fn union(&self, other: &Self) -> Self {
+        let mut set = self.clone();
+        set.union_with(other);
+        set
+    }
+
+    pub fn intersection(&self, other: &Self) -> Self {
+        let mut set = self.clone();
+        set.intersect_with(other);
+        set
+    }
+
+    pub fn difference(&self, other: &Self) -> Self {
+        let mut set = self.clone();
+        set.difference_with(other);
+        set
+    }
+
+    pub fn symmetric_difference(&self, other: &Self) -> Self {
+        let mut set = self.clone();
+        set.symmetric_difference_with(other);
+        set
+    }
+
+    pub fn is_subset(&self, other: &Self) -> bool {
+        self.blocks
+            .iter()
+            .enumerate()
+            .all(|(i, block)| block & !other.blocks.get(i).copied().unwrap_or(0) == 0)
+    }
+
+    pub fn is_superset(&self, other: &Self) -> bool {
+        other.is_subset(self)
+    }
+
+    pub fn is_disjoint(&self, other: &Self) -> bool {
+        self.blocks
+            .iter()
+            .zip(other.blocks.iter())
+            .all(|(block, other)| block & other == 0)
+    }
+
+    fn position(id: RegistryId<R>) -> (usize, u64) {
+        let index = id.numeric() as usize;
+        (index / BLOCK_BITS, 1 << (index % BLOCK_BITS))
+    }
+}
+
+impl<R: Registry> RegistryIdFilter<R> for RegistryIdSet<R> {
+    fn contains(&self, id: &RegistryId<R>) -> bool {
+        RegistryIdSet::contains(self, id)
+    }
+}
+
+impl<R: Registry> Clone for RegistryIdSet<R> {
+    fn clone(&self) -> Self {
+        Self {
+            blocks: self.blocks.clone(),
+            _marker: PhantomData,
+        }
+    }
+}
+
+impl<R: Registry> Default for RegistryIdSet<R> {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+impl<R: Registry> PartialEq for RegistryIdSet<R> {
+    fn eq(&self, other: &Self) -> bool {
+        self.is_subset(other) && other.is_subset(self)
+    }
+}
+
+impl<R: Registry> Eq for RegistryIdSet<R> {}
+
+impl<R: Registry> fmt::Debug for RegistryIdSet<R> {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.debug_set().entries(self.iter()).finish()
+    }
+}
+
+impl<R: Registry> FromIterator<RegistryId<R>> for RegistryIdSet<R> {
+    fn from_iter<T: IntoIterator<Item = RegistryId<R>>>(iter: T) -> Self {
+        let mut set = Self::new();
+        set.extend(iter);
+        set
+    }
+}
+
+impl<R: Registry> Extend<RegistryId<R>> for RegistryIdSet<R> {
+    fn extend<T: IntoIterator<Item = RegistryId<R>>>(&mut self, iter: T) {
+        for id in iter {
+            self.insert(id);
+        }
+    }
+}
+
+/// The ids, that have a value in the map
+impl<R: Registry, V> From<&RegistryIdMap<R, V>> for RegistryIdSet<R> {
+    fn from(value: &RegistryIdMap<R, V>) -> Self {
+        value.ids().collect()
+    }
+}
+
+/// A map with a value for every item of the registry
+pub struct RegistryIdArray<R: Registry, V> {
+    values: Vec<V>,
+    _marker: PhantomData<R>,
+}
+
+impl<R: Registry, V> RegistryIdArray<R, V> {
+    /// Creates the array with the default value for every item of the registry
+    pub fn new() -> Self
+    where
+        V: Default,
+    {
+        let mut values = Vec::with_capacity(R::count());
+        values.resize_with(R::count(), V::default);
+        Self {
+            values,
+            _marker: PhantomData,
+        }
+    }
+
+    /// Creates the array, using the function to create a value for every item of the registry
+    pub fn from_fn(f: impl FnMut(RegistryId<R>) -> V) -> Self {
+        let mut values = Vec::with_capacity(R::count());
+        values.extend(RegistryId::iter_all().map(f));
+        Self {
+            values,
+            _marker: PhantomData,
+        }
+    }
+
+    /// Creates the array with the same value for every item of the registry
+    pub fn from_elem(value: V) -> Self
+    where
+        V: Clone,
+    {
+        Self::from_fn(|_| value.clone())
+    }
+
+    pub fn get(&self, id: &RegistryId<R>) -> &V {
+        &self.values[id.clone().numeric() as usize]
+    }
+
+    pub fn get_mut(&mut self, id: &RegistryId<R>) -> &mut V {
+        &mut self.values[id.clone().numeric() as usize]
+    }
+
+    /// Replaces the value of the id, returns the old one
+    pub fn set(&mut self, id: &RegistryId<R>, value: V) -> V {
+        std::mem::replace(self.get_mut(id), value)
+    }
+
+    pub fn len(&self) -> usize {
+        self.values.len()
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.values.is_empty()
+    }
+
+    pub fn iter(&self) -> impl Iterator<Item = (RegistryId<R>, &V)> + '_ {
+        self.values.iter().enumerate().map(|(i, value)| {
+            (
+                RegistryId::from_numeric_unchecked(i as RegistryIdNumeric),
+                value,
+            )
+        })
+    }
+
+    pub fn iter_mut(&mut self) -> impl Iterator<Item = (RegistryId<R>, &mut V)> + '_ {
+        self.values.iter_mut().enumerate().map(|(i, value)| {
+            (
+                RegistryId::from_numeric_unchecked(i as RegistryIdNumeric),
+                value,
+            )
+        })
+    }
+
+    pub fn values(&self) -> core::slice::Iter<'_, V> {
+        self.values.iter()
+    }
+
+    pub fn values_mut(&mut self) -> core::slice::IterMut<'_, V> {
+        self.values.iter_mut()
+    }
+
+    /// The ids, which values match the predicate
+    pub fn filter(&self, predicate: impl Fn(&V) -> bool) -> RegistryIdSet<R> {
+        self.iter()
+            .filter(|(_, value)| predicate(value))
+            .map(|(id, _)| id)
+            .collect()
+    }
+}
+
+impl<R: Registry, V> Index<&RegistryId<R>> for RegistryIdArray<R, V> {
+    type Output = V;
+
+    fn index(&self, index: &RegistryId<R>) -> &Self::Output {
+        self.get(index)
+    }
+}
+
+impl<R: Registry, V> IndexMut<&RegistryId<R>> for RegistryIdArray<R, V> {
+    fn index_mut(&mut self, index: &RegistryId<R>) -> &mut Self::Output {
+        self.get_mut(index)
+    }
+}
+
+impl<R: Registry, V: Default> Default for RegistryIdArray<R, V> {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+impl<R: Registry, V: Clone> Clone for RegistryIdArray<R, V> {
+    fn clone(&self) -> Self {
+        Self {
+            values: self.values.clone(),
+            _marker: PhantomData,
+        }
+    }
+}
+
+impl<R: Registry, V: fmt::Debug> fmt::Debug for RegistryIdArray<R, V> {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.debug_map().entries(self.iter()).finish()
+    }
+}
+
+impl<R: Registry, V> RegistryIdArray<R, V> {
+    /// Fails with the items, that are not used by the world, if the registry is local,
+    /// see [`RegistryItemSet`](crate::RegistryItemSet)
+    pub fn try_from_converted(
+        value: ConvertedRegistryIdMap<R, V>,
+    ) -> Result<Self, RegistryValidationError> {
+        if let Some(ids) = value.local_numerics() {
+            let missing = (0..R::count() as RegistryIdNumeric)
+                .filter(|id| ids.binary_search(id).is_err())
+                .map(RegistryMissingEntry::new::<R, V>)
+                .collect::<Vec<_>>();
+            if !missing.is_empty() {
+                return Err(RegistryValidationError {
+                    missing,
+                    skipped: vec![],
+                });
+            }
+        }
+        Ok(Self {
+            values: value.into_values(),
+            _marker: PhantomData,
+        })
+    }
+}
+
+/// Panics if the registry is local and the world doesn't use every item,
+/// see [`RegistryIdArray::try_from_converted`]
+impl<R: Registry, V> From<ConvertedRegistryIdMap<R, V>> for RegistryIdArray<R, V> {
+    fn from(value: ConvertedRegistryIdMap<R, V>) -> Self {
+        Self::try_from_converted(value).unwrap_or_else(|err| panic!("{}", err))
+    }
+}
+
+/// Fails with the missing entries, if not every item has a value in the map
+impl<R: Registry, V> TryFrom<RegistryIdMap<R, V>> for RegistryIdArray<R, V> {
+    type Error = RegistryValidationError;
+
+    fn try_from(value: RegistryIdMap<R, V>) -> Result<Self, Self::Error> {
+        let missing = value.missing();
+        if missing.is_empty() {
+            Self::try_from_converted(value.convert())
+        } else {
+            Err(RegistryValidationError {
+                missing,
+                skipped: vec![],
+            })
+        }
+    }
+}
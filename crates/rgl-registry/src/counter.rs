@@ -0,0 +1,320 @@
+use std::{collections::BTreeMap, panic::Location};
+
+use crate::{RegistryIdNumeric, RegistryName, RegistryNameCollision, RegistryNameSource};
+
+/// Keeps track of every item key registered for a registry.
+///
+/// Keys are collected by `#[ctor]` functions in an unspecified order,
+/// so the numeric ids are assigned only when the counter is frozen:
+/// the keys are sorted and an id of an item is its index in the sorted list.
+/// This way ids do not depend on the initialization order and stay the same between builds,
+/// as long as the set of items doesn't change.
+///
+/// Keys reserved after the counter was frozen (items defined at runtime)
+/// are not sorted and get the ids after the ids of the frozen keys in the order of reservation.
+///
+/// The counter also mirrors the names of the items, that were registered in the name cell
+/// ([`RegistryNameCell`](crate::RegistryNameCell)),
+/// so they can be used in places without access to the world, like serialization.
+/// The names are looked up by their full form, see [`RegistryName`].
+///
+/// The parents of the items are stored here as well, see [`RegistryId::is_a`](crate::RegistryId::is_a).
+///
+/// The static counter of the registry has only the keys and the declared names and parents,
+/// every app works with its own copy, see [`RegistryScope`](crate::RegistryScope).
+///
+/// The amount of the items is checked when the counter is frozen and when a dynamic key is reserved,
+/// it must fit into the id type of the registry and must be less than [`RegistryIdNumeric::MAX`],
+/// which is reserved for [`UnknownRegistryId::NONE`](crate::UnknownRegistryId::NONE)
+#[doc(hidden)]
+#[derive(Clone)]
+pub struct RegistryIdCounter {
+    registry: &'static str,
+    fits: fn(RegistryIdNumeric) -> bool,
+    keys: Vec<&'static str>,
+    dynamic_keys: Vec<&'static str>,
+    declared_names: Vec<(&'static str, &'static str)>,
+    declared_parents: Vec<(&'static str, &'static str)>,
+    frozen: bool,
+    sealed: bool,
+    names: BTreeMap<RegistryIdNumeric, &'static str>,
+    name_locations: BTreeMap<RegistryIdNumeric, &'static Location<'static>>,
+    name_ids: BTreeMap<String, RegistryIdNumeric>,
+    parents: BTreeMap<RegistryIdNumeric, RegistryIdNumeric>,
+}
+
+impl RegistryIdCounter {
+    /// Creates a counter of the registry with the given name,
+    /// `fits` returns true if the numeric id can be converted into the id type of the registry
+    pub const fn new(registry: &'static str, fits: fn(RegistryIdNumeric) -> bool) -> Self {
+        Self {
+            registry,
+            fits,
+            keys: Vec::new(),
+            dynamic_keys: Vec::new(),
+            declared_names: Vec::new(),
+            declared_parents: Vec::new(),
+            frozen: false,
+            sealed: false,
+            names: BTreeMap::new(),
+            name_locations: BTreeMap::new(),
+            name_ids: BTreeMap::new(),
+            parents: BTreeMap::new(),
+        }
+    }
+
+    /// Adds a new key, panics if the counter is already frozen
+    pub fn reserve(&mut self, key: &'static str) {
+        if self.frozen {
+            panic!(
+                "Registry item {} can not be reserved after the registry ids were assigned",
+                key
+            );
+        }
+        self.keys.push(key);
+    }
+
+    /// Declares the name of the key, it will be set when the counter is frozen.
+    /// Panics if the counter is already frozen
+    pub fn declare_name(&mut self, key: &'static str, name: &'static str) {
+        if self.frozen {
+            panic!(
+                "Name {} of registry item {} can not be declared after the registry ids were assigned",
+                name, key
+            );
+        }
+        self.declared_names.push((key, name));
+    }
+
+    /// Declares the parent of the key, it will be set when the counter is frozen.
+    /// Panics if the counter is already frozen
+    pub fn declare_parent(&mut self, key: &'static str, parent: &'static str) {
+        if self.frozen {
+            panic!(
+                "Parent {} of registry item {} can not be declared after the registry ids were assigned",
+                parent, key
+            );
+        }
+        self.declared_parents.push((key, parent));
+    }
+
+    /// Sorts the keys, after this no more keys can be reserved
+    pub fn freeze(&mut self) {
+        if self.frozen {
+            return;
+        }
+        self.keys.sort_unstable();
+        if let Some(duplicate) = self.keys.windows(2).find(|keys| keys[0] == keys[1]) {
+            panic!("Registry item {} is reserved twice", duplicate[0]);
+        }
+        self.check_capacity(self.keys.len());
+        self.frozen = true;
+        for (id, name) in self.declared_names().collect::<Vec<_>>() {
+            if let Err(other) = self.set_name(id, name, None) {
+                panic!("{}", self.collision(name, other, id, None));
+            }
+        }
+        for (key, parent) in std::mem::take(&mut self.declared_parents) {
+            let id = self.keys.binary_search(&key);
+            let parent_id = self.keys.binary_search(&parent);
+            match (id, parent_id) {
+                (Ok(id), Ok(parent_id)) => {
+                    self.set_parent(id as RegistryIdNumeric, parent_id as RegistryIdNumeric)
+                }
+                _ => panic!(
+                    "Parent {} is declared for registry item {}, but one of them is not reserved",
+                    parent, key
+                ),
+            }
+        }
+    }
+
+    /// Returns a copy of the frozen counter for a [`RegistryScope`](crate::RegistryScope)
+    pub fn scoped(&mut self) -> Self {
+        self.freeze();
+        self.clone()
+    }
+
+    /// Returns the numeric ids and the names, that were declared with [`RegistryIdCounter::declare_name`],
+    /// freezes the counter if it wasn't frozen
+    pub fn declared_names(
+        &mut self,
+    ) -> impl Iterator<Item = (RegistryIdNumeric, &'static str)> + '_ {
+        self.freeze();
+        self.declared_names.iter().map(|(key, name)| {
+            let id = self.keys.binary_search(key).unwrap_or_else(|_| {
+                panic!(
+                    "Name {} is declared for not reserved registry item {}",
+                    name, key
+                )
+            });
+            (id as RegistryIdNumeric, *name)
+        })
+    }
+
+    pub fn is_frozen(&self) -> bool {
+        self.frozen
+    }
+
+    /// Forbids reserving dynamic keys, the data cells don't have room for them anymore
+    pub fn seal(&mut self) {
+        self.sealed = true;
+    }
+
+    /// Adds a new key after the frozen ones and returns its numeric id,
+    /// freezes the counter if it wasn't frozen
+    pub fn reserve_dynamic(&mut self, key: &'static str) -> RegistryIdNumeric {
+        if self.sealed {
+            panic!(
+                "Registry item {} can not be defined at runtime after the data cells of registry {} were converted",
+                key, self.registry
+            );
+        }
+        if self.numeric(key).is_some() {
+            panic!("Registry item {} is reserved twice", key);
+        }
+        let id = self.count();
+        self.check_capacity(id + 1);
+        self.dynamic_keys.push(key);
+        id as RegistryIdNumeric
+    }
+
+    /// Panics if the registry can not have the given amount of items
+    fn check_capacity(&self, count: usize) {
+        let fits = count == 0
+            || (count <= RegistryIdNumeric::MAX as usize
+                && (self.fits)((count - 1) as RegistryIdNumeric));
+        if !fits {
+            panic!(
+                "Registry {} has {} items, that is more than its id type can hold",
+                self.registry, count
+            );
+        }
+    }
+
+    /// Returns the name of the registry
+    pub fn registry(&self) -> &'static str {
+        self.registry
+    }
+
+    /// Returns the numeric id of the key, freezes the counter if it wasn't frozen
+    pub fn numeric(&mut self, key: &str) -> Option<RegistryIdNumeric> {
+        self.freeze();
+        self.keys
+            .binary_search(&key)
+            .ok()
+            .or_else(|| {
+                self.dynamic_keys
+                    .iter()
+                    .position(|dynamic_key| *dynamic_key == key)
+                    .map(|index| index + self.keys.len())
+            })
+            .map(|index| index as RegistryIdNumeric)
+    }
+
+    /// Returns the key of the numeric id, freezes the counter if it wasn't frozen
+    pub fn key(&mut self, id: RegistryIdNumeric) -> Option<&'static str> {
+        self.freeze();
+        self.keys
+            .iter()
+            .chain(self.dynamic_keys.iter())
+            .nth(id as usize)
+            .copied()
+    }
+
+    /// Returns the amount of reserved keys, freezes the counter if it wasn't frozen
+    pub fn count(&mut self) -> usize {
+        self.freeze();
+        self.keys.len() + self.dynamic_keys.len()
+    }
+
+    /// Sets the name of the numeric id, the location is the place, where the name was registered.
+    /// If another id already has the same name, the name is not set and the other id is returned
+    pub fn set_name(
+        &mut self,
+        id: RegistryIdNumeric,
+        name: &'static str,
+        location: Option<&'static Location<'static>>,
+    ) -> Result<(), RegistryIdNumeric> {
+        let full_name = RegistryName::parse(name).to_string();
+        match self.name_ids.get(&full_name) {
+            Some(&other) if other != id => return Err(other),
+            _ => {}
+        }
+        if let Some(old_name) = self.names.insert(id, name) {
+            self.name_ids
+                .remove(&RegistryName::parse(old_name).to_string());
+        }
+        self.name_ids.insert(full_name, id);
+        match location {
+            Some(location) => self.name_locations.insert(id, location),
+            None => self.name_locations.remove(&id),
+        };
+        Ok(())
+    }
+
+    /// Describes the collision of the name, that was returned by [`RegistryIdCounter::set_name`]
+    pub fn collision(
+        &mut self,
+        name: &'static str,
+        first: RegistryIdNumeric,
+        second: RegistryIdNumeric,
+        location: Option<&'static Location<'static>>,
+    ) -> RegistryNameCollision {
+        RegistryNameCollision {
+            name,
+            registry: self.registry,
+            first: self.name_source(first),
+            second: RegistryNameSource {
+                key: self.key(second),
+                location,
+            },
+        }
+    }
+
+    /// Returns the key of the numeric id and the place, where its name was registered
+    pub fn name_source(&mut self, id: RegistryIdNumeric) -> RegistryNameSource {
+        RegistryNameSource {
+            key: self.key(id),
+            location: self.name_locations.get(&id).copied(),
+        }
+    }
+
+    /// Returns the name of the numeric id
+    pub fn name(&self, id: RegistryIdNumeric) -> Option<&'static str> {
+        self.names.get(&id).copied()
+    }
+
+    /// Sets the parent of the numeric id, panics if the id would become its own ancestor
+    pub fn set_parent(&mut self, id: RegistryIdNumeric, parent: RegistryIdNumeric) {
+        if self.is_a(parent, id) {
+            panic!(
+                "Registry item {} can not be a child of {}, it would be its own ancestor",
+                self.key(id).unwrap_or("<unknown>"),
+                self.key(parent).unwrap_or("<unknown>")
+            );
+        }
+        self.parents.insert(id, parent);
+    }
+
+    pub fn parent(&self, id: RegistryIdNumeric) -> Option<RegistryIdNumeric> {
+        self.parents.get(&id).copied()
+    }
+
+    /// Returns the id followed by its ancestors, the nearest one first
+    pub fn ancestors(&self, id: RegistryIdNumeric) -> impl Iterator<Item = RegistryIdNumeric> + '_ {
+        std::iter::successors(Some(id), |&id| self.parent(id))
+    }
+
+    /// Returns true if the ancestor is the id itself or one of its ancestors
+    pub fn is_a(&self, id: RegistryIdNumeric, ancestor: RegistryIdNumeric) -> bool {
+        self.ancestors(id).any(|id| id == ancestor)
+    }
+
+    /// Returns the numeric id with the given full or short name
+    pub fn name_id(&self, name: &str) -> Option<RegistryIdNumeric> {
+        self.name_ids
+            .get(&RegistryName::parse(name).to_string())
+            .copied()
+    }
+}
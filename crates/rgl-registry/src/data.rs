@@ -1,17 +1,28 @@
 use std::{
-    any::type_name,
+    collections::BTreeMap,
     hash::{BuildHasher, Hash},
     marker::PhantomData,
 };
 
-use bevy::{ecs::system::Resource, log::error, utils::hashbrown::HashMap};
+use bevy::{
+    ecs::{system::Resource, world::World},
+    utils::hashbrown::HashMap,
+};
 
-use crate::{id::RegistryId, Registry, RegistryItem};
+use crate::{
+    change::record_change, id::RegistryId, Registry, RegistryDataChangeKind, RegistryIdNumeric,
+    RegistryItem, RegistryItemName, RegistryItemSet, RegistryMapChanges, RegistryMissingEntry,
+    RegistryValidationError,
+};
 
 pub trait RegistryMapInsert<K, V> {
     fn insert(&mut self, key: K, value: V) -> Option<V>;
 }
 
+pub trait RegistryMapRemove<K, V> {
+    fn remove(&mut self, key: &K) -> Option<V>;
+}
+
 pub trait RegistryMapGet<K, V> {
     fn get(&self, key: &K) -> Option<&V>;
 }
@@ -23,7 +34,16 @@ pub trait RegistryMapGetMut<K, V> {
 pub trait RegistryMapConvert {
     type Converted;
 
+    /// Returns the entries, that must be filled before the conversion
+    fn missing(&self) -> Vec<RegistryMissingEntry> {
+        vec![]
+    }
+
+    /// Converts the map, can panic if [`RegistryMapConvert::missing`] is not empty
     fn convert(self) -> Self::Converted;
+
+    /// Restricts the map to the items used by the world, if the registry is local, see [`RegistryItemSet`]
+    fn restrict(&mut self, _world: &World) {}
 }
 
 impl<K, V, S> RegistryMapInsert<K, V> for HashMap<K, V, S>
@@ -36,6 +56,16 @@ where
     }
 }
 
+impl<K, V, S> RegistryMapRemove<K, V> for HashMap<K, V, S>
+where
+    K: Eq + Hash,
+    S: BuildHasher,
+{
+    fn remove(&mut self, key: &K) -> Option<V> {
+        self.remove(key)
+    }
+}
+
 impl<K, V, S> RegistryMapGet<K, V> for HashMap<K, V, S>
 where
     K: Eq + Hash,
@@ -66,6 +96,11 @@ impl<K, V, S> RegistryMapConvert for HashMap<K, V, S> {
 
 pub struct RegistryIdMap<R: Registry, V> {
     objects: Vec<Option<V>>,
+    default: Option<Box<dyn Fn() -> V + Sync + Send>>,
+    inherit: Option<fn(&V) -> V>,
+    changes: BTreeMap<RegistryIdNumeric, RegistryDataChangeKind>,
+    local: Option<Vec<RegistryIdNumeric>>,
+    optional: bool,
     _marker: PhantomData<R>,
 }
 
@@ -75,10 +110,34 @@ impl<R: Registry, V> RegistryMapInsert<RegistryId<R>, V> for RegistryIdMap<R, V>
         if index >= self.objects.len() {
             self.objects.resize_with(index + 1, || None);
         }
-        self.objects
-            .get_mut(index)
-            .map(|it| std::mem::replace(it, Some(value)))
-            .flatten()
+        let old = self.objects.get_mut(index).and_then(|it| it.replace(value));
+        self.record_change(
+            index,
+            match old {
+                Some(_) => RegistryDataChangeKind::Overwritten,
+                None => RegistryDataChangeKind::Inserted,
+            },
+        );
+        old
+    }
+}
+
+impl<R: Registry, V> RegistryMapRemove<RegistryId<R>, V> for RegistryIdMap<R, V> {
+    fn remove(&mut self, key: &RegistryId<R>) -> Option<V> {
+        let index = key.clone().numeric() as usize;
+        let old = self.objects.get_mut(index).and_then(Option::take);
+        if old.is_some() {
+            self.record_change(index, RegistryDataChangeKind::Removed);
+        }
+        old
+    }
+}
+
+impl<R: Registry, V> RegistryMapChanges<R> for RegistryIdMap<R, V> {
+    fn take_changes(&mut self) -> Vec<(RegistryId<R>, RegistryDataChangeKind)> {
+        let changes = self.changes().collect();
+        self.changes.clear();
+        changes
     }
 }
 
@@ -86,96 +145,246 @@ impl<R: Registry, V> RegistryMapGet<RegistryId<R>, V> for RegistryIdMap<R, V> {
     fn get(&self, key: &RegistryId<R>) -> Option<&V> {
         self.objects
             .get(key.clone().numeric() as usize)
-            .map(Option::as_ref)
-            .flatten()
+            .and_then(Option::as_ref)
     }
 }
 
 impl<R: Registry, V> RegistryMapGetMut<RegistryId<R>, V> for RegistryIdMap<R, V> {
     fn get_mut(&mut self, key: &RegistryId<R>) -> Option<&mut V> {
-        self.objects
-            .get_mut(key.clone().numeric() as usize)
-            .map(Option::as_mut)
-            .flatten()
+        let index = key.clone().numeric() as usize;
+        if matches!(self.objects.get(index), Some(Some(_))) {
+            self.record_change(index, RegistryDataChangeKind::Overwritten);
+        }
+        self.objects.get_mut(index).and_then(Option::as_mut)
     }
 }
 
 impl<R: Registry, V> RegistryMapConvert for RegistryIdMap<R, V> {
     type Converted = ConvertedRegistryIdMap<R, V>;
 
-    fn convert(self) -> Self::Converted {
-        let mut any_missing = false;
-
-        self.objects
-            .iter()
-            .chain(std::iter::repeat(&None).take(R::count() - self.objects.len()))
-            .enumerate()
-            .filter(|(_, v)| v.is_none())
-            .for_each(|(i, _)| {
-                error!(
-                    "There is no binding with id {} in RegistryMap for registry {}",
-                    i,
-                    type_name::<R>()
-                );
-
-                any_missing = true;
-            });
+    fn missing(&self) -> Vec<RegistryMissingEntry> {
+        if self.default.is_some() || self.optional {
+            return vec![];
+        }
+        self.items()
+            .filter(|&i| self.inherited(i).is_none())
+            .map(RegistryMissingEntry::new::<R, V>)
+            .collect()
+    }
 
-        if any_missing {
-            panic!("RegistryMap couldn't be finished");
+    fn convert(mut self) -> Self::Converted {
+        let missing = self.missing();
+        if !missing.is_empty() {
+            panic!(
+                "RegistryMap couldn't be finished: {}",
+                RegistryValidationError {
+                    missing,
+                    skipped: vec![],
+                }
+            );
         }
 
+        self.inherit();
+        if self.optional {
+            let ids = self.ids().map(RegistryId::numeric).collect::<Vec<_>>();
+            self.local = Some(match self.local.take() {
+                Some(local) => ids
+                    .into_iter()
+                    .filter(|id| local.binary_search(id).is_ok())
+                    .collect(),
+                None => ids,
+            });
+        }
+        let Self {
+            mut objects,
+            default,
+            local,
+            ..
+        } = self;
+        let fill = |v: Option<V>| v.unwrap_or_else(|| default.as_ref().unwrap()());
+        let objects = match &local {
+            Some(ids) => ids
+                .iter()
+                .map(|&i| objects.get_mut(i as usize).and_then(Option::take))
+                .map(fill)
+                .collect(),
+            None => {
+                objects.resize_with(R::count(), || None);
+                objects.into_iter().map(fill).collect()
+            }
+        };
         ConvertedRegistryIdMap {
-            objects: self.objects.into_iter().map(Option::unwrap).collect(),
+            objects,
+            local,
             _marker: PhantomData,
         }
     }
+
+    fn restrict(&mut self, world: &World) {
+        self.local = RegistryItemSet::<R>::local_numerics(world);
+    }
 }
 
 impl<R: Registry, V> RegistryIdMap<R, V> {
+    /// Sets the function, that creates values for missing entries during the conversion
+    pub fn set_default(&mut self, default: impl Fn() -> V + Sync + Send + 'static) {
+        self.default = Some(Box::new(default));
+    }
+
+    /// Makes the values optional: the items without a value are not missing
+    /// and the converted map contains only the items with a value, like a local map
+    pub fn set_optional(&mut self) {
+        self.optional = true;
+    }
+
+    /// Sets the function, that copies the value of the nearest ancestor
+    /// to the missing entry during the conversion, see [`RegistryId::is_a`].
+    /// Inherited values are preferred over the default value
+    pub fn set_inherit(&mut self, inherit: fn(&V) -> V) {
+        self.inherit = Some(inherit);
+    }
+
     pub fn iter(&self) -> impl Iterator<Item = &V> + '_ {
         self.objects.iter().filter_map(Option::as_ref)
     }
 
+    /// Iterates over the ids and their values
+    pub fn entries(&self) -> impl Iterator<Item = (RegistryId<R>, &V)> + '_ {
+        self.objects.iter().enumerate().filter_map(|(i, v)| {
+            Some((
+                RegistryId::from_numeric_unchecked(i as RegistryIdNumeric),
+                v.as_ref()?,
+            ))
+        })
+    }
+
+    /// Iterates over the ids and mutable references to their values, every value is marked as changed
+    pub fn entries_mut(&mut self) -> impl Iterator<Item = (RegistryId<R>, &mut V)> + '_ {
+        self.record_all_changed();
+        self.objects.iter_mut().enumerate().filter_map(|(i, v)| {
+            Some((
+                RegistryId::from_numeric_unchecked(i as RegistryIdNumeric),
+                v.as_mut()?,
+            ))
+        })
+    }
+
+    /// Returns the ids, that have a value
+    pub fn ids(&self) -> impl Iterator<Item = RegistryId<R>> + '_ {
+        self.objects
+            .iter()
+            .enumerate()
+            .filter(|(_, v)| v.is_some())
+            .map(|(i, _)| RegistryId::from_numeric_unchecked(i as RegistryIdNumeric))
+    }
+
+    /// Every value is marked as changed
     pub fn iter_mut(&mut self) -> impl Iterator<Item = &mut V> + '_ {
+        self.record_all_changed();
         self.objects.iter_mut().filter_map(Option::as_mut)
     }
+
+    /// Iterates over the entries, that were inserted, overwritten or removed
+    /// since the last call of [`RegistryMapChanges::take_changes`]
+    pub fn changes(&self) -> impl Iterator<Item = (RegistryId<R>, RegistryDataChangeKind)> + '_ {
+        self.changes
+            .iter()
+            .map(|(&id, &kind)| (RegistryId::from_numeric_unchecked(id), kind))
+    }
+
+    /// Returns the value of the id or the inherited value, if the values are inherited
+    fn inherited(&self, id: RegistryIdNumeric) -> Option<&V> {
+        let value = |id: RegistryIdNumeric| self.objects.get(id as usize).and_then(Option::as_ref);
+        if self.inherit.is_none() {
+            return value(id);
+        }
+        let ancestors = R::scoped_counter().lock().ancestors(id).collect::<Vec<_>>();
+        ancestors.into_iter().find_map(value)
+    }
+
+    /// Copies the inherited values to the missing entries
+    fn inherit(&mut self) {
+        let Some(inherit) = self.inherit else {
+            return;
+        };
+        let inherited = self
+            .items()
+            .filter(|&i| !matches!(self.objects.get(i as usize), Some(Some(_))))
+            .filter_map(|i| Some((i, inherit(self.inherited(i)?))))
+            .collect::<Vec<_>>();
+        for (id, value) in inherited {
+            let index = id as usize;
+            if index >= self.objects.len() {
+                self.objects.resize_with(index + 1, || None);
+            }
+            self.objects[index] = Some(value);
+        }
+    }
+
+    /// The numeric ids of the items, that must have a value
+    fn items(&self) -> Box<dyn Iterator<Item = RegistryIdNumeric> + '_> {
+        match &self.local {
+            Some(ids) => Box::new(ids.iter().copied()),
+            None => Box::new(0..R::count() as RegistryIdNumeric),
+        }
+    }
+
+    fn record_change(&mut self, index: usize, kind: RegistryDataChangeKind) {
+        record_change(&mut self.changes, index as RegistryIdNumeric, kind);
+    }
+
+    fn record_all_changed(&mut self) {
+        for (index, value) in self.objects.iter().enumerate() {
+            if value.is_some() {
+                self.changes
+                    .entry(index as RegistryIdNumeric)
+                    .or_insert(RegistryDataChangeKind::Overwritten);
+            }
+        }
+    }
 }
 
 impl<R: Registry, V> Default for RegistryIdMap<R, V> {
     fn default() -> Self {
         Self {
             objects: vec![],
+            default: None,
+            inherit: None,
+            changes: BTreeMap::new(),
+            local: None,
+            optional: false,
             _marker: PhantomData,
         }
     }
 }
 
+/// If the registry is local, only the items used by the world have values
 #[doc(hidden)]
 pub struct ConvertedRegistryIdMap<R: Registry, V> {
     objects: Vec<V>,
+    local: Option<Vec<RegistryIdNumeric>>,
     _marker: PhantomData<R>,
 }
 
 impl<R: Registry, V> RegistryMapGet<RegistryId<R>, V> for ConvertedRegistryIdMap<R, V> {
     fn get(&self, key: &RegistryId<R>) -> Option<&V> {
-        self.objects.get(key.clone().numeric() as usize)
+        self.objects.get(self.index(key)?)
     }
 }
 
 impl<R: Registry, V> RegistryMapGetMut<RegistryId<R>, V> for ConvertedRegistryIdMap<R, V> {
     fn get_mut(&mut self, key: &RegistryId<R>) -> Option<&mut V> {
-        self.objects.get_mut(key.clone().numeric() as usize)
+        let index = self.index(key)?;
+        self.objects.get_mut(index)
     }
 }
 
 impl<R: Registry, V> RegistryMapInsert<RegistryId<R>, V> for ConvertedRegistryIdMap<R, V> {
     fn insert(&mut self, key: RegistryId<R>, value: V) -> Option<V> {
-        let index = key.numeric() as usize;
-        if self.objects.len() <= index {
-            panic!("ConvertedRegistryIdMap doesn't support creating new values");
+        match self.get_mut(&key) {
+            Some(old) => Some(std::mem::replace(old, value)),
+            None => panic!("ConvertedRegistryIdMap doesn't support creating new values"),
         }
-        Some(std::mem::replace(&mut self.objects[index], value))
     }
 }
 
@@ -184,6 +393,48 @@ impl<R: Registry, V> ConvertedRegistryIdMap<R, V> {
         self.objects.iter()
     }
 
+    /// Iterates over the ids and their values
+    pub fn entries(&self) -> impl Iterator<Item = (RegistryId<R>, &V)> + '_ {
+        let local = &self.local;
+        self.objects
+            .iter()
+            .enumerate()
+            .map(move |(i, v)| (Self::id_at(local, i), v))
+    }
+
+    /// Iterates over the ids and mutable references to their values
+    pub fn entries_mut(&mut self) -> impl Iterator<Item = (RegistryId<R>, &mut V)> + '_ {
+        let local = &self.local;
+        self.objects
+            .iter_mut()
+            .enumerate()
+            .map(move |(i, v)| (Self::id_at(local, i), v))
+    }
+
+    /// The sorted numeric ids of the items with values, if the registry is local
+    pub(crate) fn local_numerics(&self) -> Option<&[RegistryIdNumeric]> {
+        self.local.as_deref()
+    }
+
+    pub(crate) fn into_values(self) -> Vec<V> {
+        self.objects
+    }
+
+    fn index(&self, key: &RegistryId<R>) -> Option<usize> {
+        let numeric = key.clone().numeric();
+        match &self.local {
+            Some(ids) => ids.binary_search(&numeric).ok(),
+            None => Some(numeric as usize),
+        }
+    }
+
+    fn id_at(local: &Option<Vec<RegistryIdNumeric>>, index: usize) -> RegistryId<R> {
+        let numeric = local
+            .as_ref()
+            .map_or(index as RegistryIdNumeric, |ids| ids[index]);
+        RegistryId::from_numeric_unchecked(numeric)
+    }
+
     pub fn iter_mut(&mut self) -> core::slice::IterMut<'_, V> {
         self.objects.iter_mut()
     }
@@ -205,6 +456,9 @@ pub type RegistryTwoSidedDataCellId2Value<R, V> = RegistryDataCell<
     <HashMap<V, RegistryId<R>> as RegistryMapConvert>::Converted,
 >;
 
+/// The cell with names of registry items, the names are also used to serialize [`RegistryId`]
+pub type RegistryNameCell<R> = RegistryTwoSidedDataCellId2Value<R, RegistryItemName>;
+
 /// A table with 2 keys, that point to each other. The second key (named value here) will be RegistryId
 pub type RegistryTwoSidedDataCellId2Id<R1, R2> = RegistryDataCell<
     RegistryId<R1>,
@@ -259,18 +513,49 @@ impl<Id, Value, C1, C2> RegistryDataCell<Id, Value, C1, C2> {
         self.c2.get(value)
     }
 
-    /// Inserts new entry
+    /// Inserts new entry, returns the old value of the id and the other id, that had the value before.
+    /// The old value doesn't point at the id anymore and the other id loses its value,
+    /// so both sides stay one to one
     pub fn insert(&mut self, id: Id, value: Value) -> (Option<Value>, Option<Id>)
     where
-        C1: RegistryMapInsert<Id, Value>,
-        C2: RegistryMapInsert<Value, Id>,
-        Value: Clone,
-        Id: Clone,
+        C1: RegistryMapInsert<Id, Value> + RegistryMapRemove<Id, Value>,
+        C2: RegistryMapInsert<Value, Id> + RegistryMapRemove<Value, Id>,
+        Value: Clone + PartialEq,
+        Id: Clone + PartialEq,
+    {
+        let old = self.c1.insert(id.clone(), value.clone());
+        if let Some(old) = old.as_ref().filter(|old| **old != value) {
+            self.c2.remove(old);
+        }
+        let displaced = self
+            .c2
+            .insert(value, id.clone())
+            .filter(|other| *other != id);
+        if let Some(other) = &displaced {
+            self.c1.remove(other);
+        }
+        (old, displaced)
+    }
+
+    /// Returns the entries, that must be filled before the conversion
+    pub fn missing(&self) -> Vec<RegistryMissingEntry>
+    where
+        C1: RegistryMapConvert,
+        C2: RegistryMapConvert,
+    {
+        let mut missing = self.c1.missing();
+        missing.extend(self.c2.missing());
+        missing
+    }
+
+    /// Restricts the maps to the items used by the world, see [`RegistryMapConvert::restrict`]
+    pub fn restrict(&mut self, world: &World)
+    where
+        C1: RegistryMapConvert,
+        C2: RegistryMapConvert,
     {
-        (
-            self.c1.insert(id.clone(), value.clone()),
-            self.c2.insert(value.clone(), id.clone()),
-        )
+        self.c1.restrict(world);
+        self.c2.restrict(world);
     }
 
     /// Converts this registry data cell to more optimized one
@@ -0,0 +1,133 @@
+use std::{
+    ffi::OsStr,
+    fmt,
+    path::{Path, PathBuf},
+};
+
+use bevy::{
+    asset::{
+        io::{AssetReader, AssetReaderError, Reader},
+        Asset, AssetApp, AssetLoader, AsyncReadExt, LoadContext,
+    },
+    prelude::*,
+    reflect::TypePath,
+    utils::BoxedFuture,
+};
+use serde::{Deserialize, Serialize};
+
+/// A list of registry items, that are defined at runtime.
+/// Every item is reserved with [`RegistryId::new_dynamic`](crate::RegistryId::new_dynamic)
+/// and gets its name registered in the [`RegistryNameCell`](crate::RegistryNameCell).
+///
+/// The file can be written in RON or JSON, the format is chosen using the extension:
+/// ```ron
+/// (items: ["lava", "ice"])
+/// ```
+#[derive(Asset, TypePath, Serialize, Deserialize, Clone, Debug, Default, PartialEq, Eq)]
+pub struct RegistryItemsFile {
+    pub items: Vec<String>,
+}
+
+#[derive(Debug)]
+pub enum RegistryItemsFileError {
+    Io(PathBuf, std::io::Error),
+    Read(PathBuf, AssetReaderError),
+    Ron(ron::error::SpannedError),
+    Json(serde_json::Error),
+    UnknownExtension(PathBuf),
+}
+
+impl fmt::Display for RegistryItemsFileError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            Self::Io(path, err) => write!(f, "Couldn't read {}: {}", path.display(), err),
+            Self::Read(path, err) => write!(f, "Couldn't read {}: {}", path.display(), err),
+            Self::Ron(err) => write!(f, "Couldn't parse RON: {}", err),
+            Self::Json(err) => write!(f, "Couldn't parse JSON: {}", err),
+            Self::UnknownExtension(path) => write!(
+                f,
+                "Unknown extension of {}, expected .ron or .json",
+                path.display()
+            ),
+        }
+    }
+}
+
+impl std::error::Error for RegistryItemsFileError {}
+
+impl RegistryItemsFile {
+    pub fn from_ron(s: &str) -> Result<Self, RegistryItemsFileError> {
+        ron::from_str(s).map_err(RegistryItemsFileError::Ron)
+    }
+
+    pub fn from_json(s: &str) -> Result<Self, RegistryItemsFileError> {
+        serde_json::from_str(s).map_err(RegistryItemsFileError::Json)
+    }
+
+    /// Parses the contents of the file, the format is chosen using the extension of the path
+    pub fn from_slice(bytes: &[u8], path: &Path) -> Result<Self, RegistryItemsFileError> {
+        match path.extension().and_then(OsStr::to_str) {
+            Some("ron") => ron::de::from_bytes(bytes).map_err(RegistryItemsFileError::Ron),
+            Some("json") => serde_json::from_slice(bytes).map_err(RegistryItemsFileError::Json),
+            _ => Err(RegistryItemsFileError::UnknownExtension(path.to_owned())),
+        }
+    }
+
+    /// Reads the file with the reader of an asset source, the path is relative to the source
+    pub async fn read(
+        reader: &dyn AssetReader,
+        path: &Path,
+    ) -> Result<Self, RegistryItemsFileError> {
+        let mut bytes = Vec::new();
+        reader
+            .read(path)
+            .await
+            .map_err(|err| RegistryItemsFileError::Read(path.to_owned(), err))?
+            .read_to_end(&mut bytes)
+            .await
+            .map_err(|err| RegistryItemsFileError::Io(path.to_owned(), err))?;
+        Self::from_slice(&bytes, path)
+    }
+}
+
+/// Loads [`RegistryItemsFile`] from `.items.ron` and `.items.json` files.
+/// The items of a loaded file can not be registered anymore, because the registries are frozen
+/// before the assets are loaded, use [`RegistryAppExt::load_dynamic_items`](crate::RegistryAppExt::load_dynamic_items) for them
+#[derive(Default)]
+pub struct RegistryItemsFileLoader;
+
+impl AssetLoader for RegistryItemsFileLoader {
+    type Asset = RegistryItemsFile;
+    type Settings = ();
+    type Error = RegistryItemsFileError;
+
+    fn load<'a>(
+        &'a self,
+        reader: &'a mut Reader,
+        _settings: &'a Self::Settings,
+        load_context: &'a mut LoadContext,
+    ) -> BoxedFuture<'a, Result<Self::Asset, Self::Error>> {
+        Box::pin(async move {
+            let mut bytes = Vec::new();
+            reader
+                .read_to_end(&mut bytes)
+                .await
+                .map_err(|err| RegistryItemsFileError::Io(load_context.path().to_owned(), err))?;
+            RegistryItemsFile::from_slice(&bytes, load_context.path())
+        })
+    }
+
+    fn extensions(&self) -> &[&str] {
+        &["items.ron", "items.json"]
+    }
+}
+
+/// Adds [`RegistryItemsFile`] asset and [`RegistryItemsFileLoader`]
+pub struct RegistryItemsFilePlugin;
+
+impl Plugin for RegistryItemsFilePlugin {
+    fn build(&self, app: &mut App) {
+        app.init_asset::<RegistryItemsFile>()
+            .init_asset_loader::<RegistryItemsFileLoader>();
+    }
+}
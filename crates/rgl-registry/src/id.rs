@@ -1,7 +1,20 @@
+use std::{
+    fmt,
+    hash::{Hash, Hasher},
+    marker::PhantomData,
+};
+
+use bevy::reflect::{Reflect, ReflectDeserialize, ReflectSerialize};
+use serde::{de, Deserialize, Deserializer, Serialize, Serializer};
+
 use crate::*;
 
-/// A container for registry's id. Helps not to mix up different registries' ids
+/// A container for registry's id. Helps not to mix up different registries' ids.
+///
+/// Reflected as a value, so scenes store it as the name of the item, see [`RegistryNameCell`]
 #[repr(transparent)]
+#[derive(Reflect)]
+#[reflect_value(Debug, Hash, PartialEq, Serialize, Deserialize)]
 pub struct RegistryId<R: Registry>(R::Id);
 
 impl<R: Registry> RegistryId<R> {
@@ -12,6 +25,11 @@ impl<R: Registry> RegistryId<R> {
         Self(I::id())
     }
 
+    /// Reserves an id for an item, that is defined at runtime, see [`Registry::reserve_dynamic_id`]
+    pub fn new_dynamic(key: &'static str) -> Self {
+        Self(R::reserve_dynamic_id(key))
+    }
+
     pub fn is<I>(&self) -> bool
     where
         I: RegistryItem<Registry = R>,
@@ -19,6 +37,25 @@ impl<R: Registry> RegistryId<R> {
         self.0 == I::id()
     }
 
+    /// Returns the id with the given numeric representation, if the registry has it
+    pub fn from_numeric(numeric: RegistryIdNumeric) -> Option<Self> {
+        if (numeric as usize) < R::count() {
+            R::Id::try_from(numeric).ok().map(Self)
+        } else {
+            None
+        }
+    }
+
+    /// Returns the id of the numeric representation, that was taken from an id of the registry,
+    /// so it is not checked against the current scope
+    pub(crate) fn from_numeric_unchecked(numeric: RegistryIdNumeric) -> Self {
+        Self(
+            R::Id::try_from(numeric)
+                .ok()
+                .expect("Numeric ids of the registry fit into its id type"),
+        )
+    }
+
     pub fn numeric(self) -> RegistryIdNumeric {
         self.0.into()
     }
@@ -26,6 +63,92 @@ impl<R: Registry> RegistryId<R> {
     pub fn iter_all() -> impl Iterator<Item = RegistryId<R>> {
         R::iter_all().map(|id| Self(id))
     }
+
+    /// Returns the name of this id, that was registered in the [`RegistryNameCell`]
+    pub fn name(&self) -> Option<&'static str> {
+        R::scoped_counter().lock().name(self.0.clone().into())
+    }
+
+    /// Returns the parent of this item, see [`RegistryAppExt::register_item_parent`]
+    pub fn parent(&self) -> Option<Self> {
+        let numeric = R::scoped_counter().lock().parent(self.0.clone().into())?;
+        Self::from_numeric(numeric)
+    }
+
+    /// Returns true if the item is the ancestor itself or one of its descendants,
+    /// e.g. a mossy floor is a floor
+    pub fn is_a(&self, ancestor: &Self) -> bool {
+        R::scoped_counter()
+            .lock()
+            .is_a(self.0.clone().into(), ancestor.0.clone().into())
+    }
+
+    /// The same as [`RegistryId::is_a`] with the id of the [`RegistryItem`]
+    pub fn is_a_ty<I>(&self) -> bool
+    where
+        I: RegistryItem<Registry = R>,
+    {
+        self.is_a(&Self::new::<I>())
+    }
+
+    /// Returns the id, which name was registered in the [`RegistryNameCell`],
+    /// the name can be full or short, see [`RegistryName`]
+    pub fn from_name(name: &str) -> Option<Self> {
+        let numeric = R::scoped_counter().lock().name_id(name)?;
+        Self::from_numeric(numeric)
+    }
+}
+
+impl<R: Registry> Serialize for RegistryId<R> {
+    fn serialize<S>(&self, serializer: S) -> Result<S::Ok, S::Error>
+    where
+        S: Serializer,
+    {
+        match self.name() {
+            Some(name) => serializer.serialize_str(name),
+            None => Err(serde::ser::Error::custom(format!(
+                "Registry id {} of registry {} has no name",
+                self.clone().numeric(),
+                std::any::type_name::<R>()
+            ))),
+        }
+    }
+}
+
+impl<'de, R: Registry> Deserialize<'de> for RegistryId<R> {
+    fn deserialize<D>(deserializer: D) -> Result<Self, D::Error>
+    where
+        D: Deserializer<'de>,
+    {
+        deserializer.deserialize_str(RegistryIdVisitor(PhantomData))
+    }
+}
+
+struct RegistryIdVisitor<R>(PhantomData<R>);
+
+impl<'de, R: Registry> de::Visitor<'de> for RegistryIdVisitor<R> {
+    type Value = RegistryId<R>;
+
+    fn expecting(&self, formatter: &mut fmt::Formatter) -> fmt::Result {
+        write!(
+            formatter,
+            "a name of an item of registry {}",
+            std::any::type_name::<R>()
+        )
+    }
+
+    fn visit_str<E>(self, v: &str) -> Result<Self::Value, E>
+    where
+        E: de::Error,
+    {
+        RegistryId::from_name(v).ok_or_else(|| {
+            E::custom(format!(
+                "There is no item named {} in registry {}",
+                v,
+                std::any::type_name::<R>()
+            ))
+        })
+    }
 }
 
 impl<R: Registry> PartialEq for RegistryId<R> {
@@ -44,6 +167,202 @@ impl<R: Registry> Clone for RegistryId<R> {
 
 impl<R: Registry> Copy for RegistryId<R> where R::Id: Copy {}
 
+impl<R: Registry> Hash for RegistryId<R> {
+    fn hash<H: Hasher>(&self, state: &mut H) {
+        self.clone().numeric().hash(state);
+    }
+}
+
+impl<R: Registry> fmt::Debug for RegistryId<R> {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self.name() {
+            Some(name) => write!(f, "RegistryId({})", name),
+            None => write!(f, "RegistryId({})", self.clone().numeric()),
+        }
+    }
+}
+
+/// An id of an item of any [`ChildRegistry`], that remembers its registry,
+/// so it can be checked and turned back into [`RegistryId`].
+/// Can be used in components, that point at items of different registries.
+///
+/// Displayed as `registry:item` using the names of the registry and the item,
+/// see [`RegistryNameCell`]. Serialized and reflected as the pair of the names,
+/// the registry and the item without a name are serialized with their keys
+#[derive(Clone, Copy, Reflect)]
+#[reflect_value(Debug, Hash, PartialEq, Serialize, Deserialize)]
+pub struct AnyRegistryId {
+    registry: RegistryId<Registries>,
+    id: RegistryIdNumeric,
+    counter: fn() -> RegistryCounterRef,
+}
+
+impl AnyRegistryId {
+    pub fn new<I>() -> Self
+    where
+        I: RegistryItem,
+        I::Registry: ChildRegistry,
+    {
+        Self::from(RegistryId::<I::Registry>::new::<I>())
+    }
+
+    /// The id of the registry of the item
+    pub fn registry(&self) -> RegistryId<Registries> {
+        self.registry
+    }
+
+    pub fn numeric(&self) -> RegistryIdNumeric {
+        self.id
+    }
+
+    pub fn is_registry<R: ChildRegistry>(&self) -> bool {
+        self.registry.is::<R>()
+    }
+
+    /// Unlike [`UnknownRegistryId::is`], the registry of the item is checked too
+    pub fn is<I>(&self) -> bool
+    where
+        I: RegistryItem,
+        I::Registry: ChildRegistry,
+    {
+        self.is_registry::<I::Registry>() && self.id == I::id().into()
+    }
+
+    /// Returns the id, if it belongs to the registry
+    pub fn downcast<R: ChildRegistry>(&self) -> Option<RegistryId<R>> {
+        if self.is_registry::<R>() {
+            RegistryId::from_numeric(self.id)
+        } else {
+            None
+        }
+    }
+
+    /// Returns the name of the item, that was registered in the [`RegistryNameCell`] of its registry
+    pub fn name(&self) -> Option<&'static str> {
+        (self.counter)().lock().name(self.id)
+    }
+
+    /// Returns the id of the item of the registry, both can be given by their names or keys
+    pub fn from_names(registry: &str, item: &str) -> Option<Self> {
+        let (registry, key) = {
+            let counter = Registries::scoped_counter();
+            let mut counter = counter.lock();
+            let registry = counter
+                .name_id(registry)
+                .or_else(|| counter.numeric(registry))?;
+            (registry, counter.key(registry)?)
+        };
+        let counter = introspection::child_registry_counter(key)?;
+        let id = {
+            let counter = counter();
+            let mut counter = counter.lock();
+            counter.name_id(item).or_else(|| counter.numeric(item))?
+        };
+        Some(Self {
+            registry: RegistryId::from_numeric_unchecked(registry),
+            id,
+            counter,
+        })
+    }
+
+    /// The names or the keys of the registry and the item
+    fn names(&self) -> (Option<&'static str>, Option<&'static str>) {
+        let registry = self.registry.name().or_else(|| {
+            Registries::scoped_counter()
+                .lock()
+                .key(self.registry.numeric())
+        });
+        let item = {
+            let counter = (self.counter)();
+            let mut counter = counter.lock();
+            counter.name(self.id).or_else(|| counter.key(self.id))
+        };
+        (registry, item)
+    }
+}
+
+impl<R: ChildRegistry> From<RegistryId<R>> for AnyRegistryId {
+    fn from(value: RegistryId<R>) -> Self {
+        Self {
+            registry: RegistryId::new::<R>(),
+            id: value.numeric(),
+            counter: R::scoped_counter,
+        }
+    }
+}
+
+impl PartialEq for AnyRegistryId {
+    fn eq(&self, other: &Self) -> bool {
+        self.registry == other.registry && self.id == other.id
+    }
+}
+
+impl Eq for AnyRegistryId {}
+
+impl Hash for AnyRegistryId {
+    fn hash<H: Hasher>(&self, state: &mut H) {
+        self.registry.hash(state);
+        self.id.hash(state);
+    }
+}
+
+/// Formats the registry and the item as quoted strings: `"test":"core:floor"`,
+/// so names with a namespace can't be confused with the separator.
+/// Items and registries without a name are displayed with their keys
+impl fmt::Display for AnyRegistryId {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        fn quoted(f: &mut fmt::Formatter<'_>, name: Option<&str>) -> fmt::Result {
+            match name {
+                Some(name) => write!(f, "{:?}", name),
+                None => f.write_str("<unknown>"),
+            }
+        }
+
+        let (registry, item) = self.names();
+        quoted(f, registry)?;
+        f.write_str(":")?;
+        quoted(f, item)
+    }
+}
+
+impl fmt::Debug for AnyRegistryId {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(f, "AnyRegistryId({})", self)
+    }
+}
+
+impl Serialize for AnyRegistryId {
+    fn serialize<S>(&self, serializer: S) -> Result<S::Ok, S::Error>
+    where
+        S: Serializer,
+    {
+        match self.names() {
+            (Some(registry), Some(item)) => (registry, item).serialize(serializer),
+            _ => Err(serde::ser::Error::custom(format!(
+                "Registry id {} of registry {} has no key",
+                self.id,
+                self.registry.numeric()
+            ))),
+        }
+    }
+}
+
+impl<'de> Deserialize<'de> for AnyRegistryId {
+    fn deserialize<D>(deserializer: D) -> Result<Self, D::Error>
+    where
+        D: Deserializer<'de>,
+    {
+        let (registry, item) = <(String, String)>::deserialize(deserializer)?;
+        Self::from_names(&registry, &item).ok_or_else(|| {
+            de::Error::custom(format!(
+                "There is no item {} in registry {}",
+                item, registry
+            ))
+        })
+    }
+}
+
+/// An id of an item of unknown registry, use [`AnyRegistryId`] if the registry must be checked
 #[repr(transparent)]
 #[derive(Clone, Copy, PartialEq, Eq, Hash)]
 pub struct UnknownRegistryId(RegistryIdNumeric);
@@ -67,12 +386,90 @@ impl UnknownRegistryId {
     }
 }
 
+impl UnknownRegistryId {
+    /// Serializes the id as the name of the item of the given registry, like [`RegistryId`],
+    /// items without a name are serialized as their numeric representation.
+    /// Can be used as `#[serde(serialize_with = "UnknownRegistryId::serialize_named::<SomeRegistry, _>")]`
+    pub fn serialize_named<R, S>(&self, serializer: S) -> Result<S::Ok, S::Error>
+    where
+        R: Registry,
+        S: Serializer,
+    {
+        if *self == Self::NONE {
+            return serializer.serialize_none();
+        }
+        match RegistryId::<R>::from_numeric(self.0).and_then(|id| id.name()) {
+            Some(name) => serializer.serialize_some(name),
+            None => serializer.serialize_some(&self.0),
+        }
+    }
+
+    /// Deserializes the id, that was serialized with [`UnknownRegistryId::serialize_named`]
+    pub fn deserialize_named<'de, R, D>(deserializer: D) -> Result<Self, D::Error>
+    where
+        R: Registry,
+        D: Deserializer<'de>,
+    {
+        #[derive(Deserialize)]
+        #[serde(untagged)]
+        enum Named {
+            Name(String),
+            Numeric(RegistryIdNumeric),
+        }
+
+        match Option::<Named>::deserialize(deserializer)? {
+            None => Ok(Self::NONE),
+            Some(Named::Numeric(numeric)) => Ok(Self(numeric)),
+            Some(Named::Name(name)) => RegistryId::<R>::from_name(&name)
+                .map(Self::from)
+                .ok_or_else(|| {
+                    de::Error::custom(format!(
+                        "There is no item named {} in registry {}",
+                        name,
+                        std::any::type_name::<R>()
+                    ))
+                }),
+        }
+    }
+}
+
+/// [`UnknownRegistryId`] doesn't know its registry, so it can not be resolved to a name
+/// and is serialized as its numeric representation, [`UnknownRegistryId::NONE`] is serialized as none.
+/// Use [`UnknownRegistryId::serialize_named`] if the registry is known
+impl Serialize for UnknownRegistryId {
+    fn serialize<S>(&self, serializer: S) -> Result<S::Ok, S::Error>
+    where
+        S: Serializer,
+    {
+        if *self == Self::NONE {
+            serializer.serialize_none()
+        } else {
+            serializer.serialize_some(&self.0)
+        }
+    }
+}
+
+impl<'de> Deserialize<'de> for UnknownRegistryId {
+    fn deserialize<D>(deserializer: D) -> Result<Self, D::Error>
+    where
+        D: Deserializer<'de>,
+    {
+        Ok(Option::<RegistryIdNumeric>::deserialize(deserializer)?.map_or(Self::NONE, Self))
+    }
+}
+
 impl<R: Registry> From<RegistryId<R>> for UnknownRegistryId {
     fn from(value: RegistryId<R>) -> Self {
         Self(value.0.into())
     }
 }
 
+impl From<AnyRegistryId> for UnknownRegistryId {
+    fn from(value: AnyRegistryId) -> Self {
+        Self(value.id)
+    }
+}
+
 impl<R: Registry> From<Option<RegistryId<R>>> for UnknownRegistryId {
     fn from(value: Option<RegistryId<R>>) -> Self {
         match value {
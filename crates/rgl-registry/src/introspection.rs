@@ -0,0 +1,204 @@
+use std::{
+    any::{type_name, TypeId},
+    fmt,
+};
+
+use bevy::ecs::world::World;
+use serde::Serialize;
+
+use crate::{
+    app::registered_cells, ChildRegistry, Registries, Registry, RegistryCounterRef, RegistryId,
+    RegistryIdNumeric, RegistryItem, RegistryItemSet, RegistryScope,
+};
+
+type CounterFn = fn() -> RegistryCounterRef;
+
+/// A child registry linked into the binary, is added by [`new_registry`](crate::new_registry)
+/// and the `Registry` derive
+#[derive(Clone, Copy)]
+struct ChildRegistryEntry {
+    key: &'static str,
+    type_name: &'static str,
+    id_type: TypeId,
+    counter: CounterFn,
+    members: fn(&World) -> Option<Vec<RegistryIdNumeric>>,
+}
+
+static CHILD_REGISTRIES: parking_lot::Mutex<Vec<ChildRegistryEntry>> =
+    parking_lot::Mutex::new(Vec::new());
+
+pub(crate) fn add_child_registry<R: ChildRegistry>() {
+    CHILD_REGISTRIES.lock().push(ChildRegistryEntry {
+        key: <R as RegistryItem>::KEY,
+        type_name: type_name::<R>(),
+        id_type: TypeId::of::<RegistryId<R>>(),
+        counter: R::scoped_counter,
+        members: members::<R>,
+    });
+}
+
+/// Keys and id counters of every child registry linked into the binary
+pub(crate) fn child_registries() -> Vec<(&'static str, CounterFn)> {
+    CHILD_REGISTRIES
+        .lock()
+        .iter()
+        .map(|entry| (entry.key, entry.counter))
+        .collect()
+}
+
+/// The id counter of the child registry with the given key
+pub(crate) fn child_registry_counter(key: &str) -> Option<CounterFn> {
+    CHILD_REGISTRIES
+        .lock()
+        .iter()
+        .find(|entry| entry.key == key)
+        .map(|entry| entry.counter)
+}
+
+fn members<R: Registry>(world: &World) -> Option<Vec<RegistryIdNumeric>> {
+    world
+        .get_resource::<RegistryItemSet<R>>()
+        .map(|set| set.iter().map(RegistryId::numeric).collect())
+}
+
+/// A data cell registered in the app for the items of the registry
+#[derive(Clone, Debug, PartialEq, Eq, Serialize)]
+pub struct RegistryCellInfo {
+    /// Type name of the value
+    pub value: &'static str,
+    /// Type name of the data cell
+    pub cell: &'static str,
+    /// The cell can also be looked up by the value
+    pub two_sided: bool,
+}
+
+#[derive(Clone, Debug, PartialEq, Eq, Serialize)]
+pub struct RegistryItemInfo {
+    pub id: RegistryIdNumeric,
+    /// Key of the item, that was used to assign the id
+    pub key: &'static str,
+    /// Name of the item from the [`RegistryNameCell`](crate::RegistryNameCell)
+    pub name: Option<&'static str>,
+    /// Numeric id of the parent, see [`RegistryId::is_a`]
+    pub parent: Option<RegistryIdNumeric>,
+    /// The item is a member of the [`RegistryItemSet`] of the world
+    pub used: bool,
+}
+
+#[derive(Clone, Debug, PartialEq, Eq, Serialize)]
+pub struct RegistryInfo {
+    /// Numeric id of the registry in [`Registries`]
+    pub id: RegistryIdNumeric,
+    /// Key of the registry, that was used to assign the id
+    pub key: &'static str,
+    /// Name of the registry from the name cell of [`Registries`]
+    pub name: Option<&'static str>,
+    pub type_name: &'static str,
+    pub items: Vec<RegistryItemInfo>,
+    pub cells: Vec<RegistryCellInfo>,
+}
+
+/// Every child registry of [`Registries`] with its items and the data cells of the world.
+///
+/// Can be included in bug reports: [`fmt::Display`] writes it as text
+/// and [`RegistryDump::to_json`] as JSON
+#[derive(Clone, Debug, Default, PartialEq, Eq, Serialize)]
+pub struct RegistryDump {
+    pub registries: Vec<RegistryInfo>,
+}
+
+impl RegistryDump {
+    /// Collects the registries sorted by their ids, the items and data cells are taken from the world
+    pub fn new(world: &World) -> Self {
+        match world.get_resource::<RegistryScope>() {
+            Some(scope) => scope.enter(|| Self::collect(world)),
+            None => Self::collect(world),
+        }
+    }
+
+    fn collect(world: &World) -> Self {
+        let entries = CHILD_REGISTRIES.lock().clone();
+        let cells = registered_cells(world);
+        let registries = RegistryId::<Registries>::iter_all()
+            .filter_map(|id| {
+                let key = Registries::scoped_counter().lock().key(id.numeric())?;
+                let entry = entries.iter().find(|entry| entry.key == key)?;
+                Some(RegistryInfo {
+                    id: id.numeric(),
+                    key,
+                    name: id.name(),
+                    type_name: entry.type_name,
+                    items: items(entry, world),
+                    cells: cells
+                        .iter()
+                        .filter(|(key, _)| *key == entry.id_type)
+                        .map(|(_, cell)| cell.clone())
+                        .collect(),
+                })
+            })
+            .collect();
+        Self { registries }
+    }
+
+    pub fn get(&self, key: &str) -> Option<&RegistryInfo> {
+        self.registries.iter().find(|info| info.key == key)
+    }
+
+    pub fn to_json(&self) -> String {
+        serde_json::to_string_pretty(self).expect("Registry dump is always serializable")
+    }
+}
+
+fn items(entry: &ChildRegistryEntry, world: &World) -> Vec<RegistryItemInfo> {
+    let members = (entry.members)(world).unwrap_or_default();
+    let counter = (entry.counter)();
+    let mut counter = counter.lock();
+    (0..counter.count() as RegistryIdNumeric)
+        .map(|id| RegistryItemInfo {
+            id,
+            key: counter.key(id).unwrap(),
+            name: counter.name(id),
+            parent: counter.parent(id),
+            used: members.contains(&id),
+        })
+        .collect()
+}
+
+impl fmt::Display for RegistryDump {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        for registry in self.registries.iter() {
+            write!(f, "Registry {} ", registry.id)?;
+            if let Some(name) = registry.name {
+                write!(f, "{} ", name)?;
+            }
+            writeln!(
+                f,
+                "({}), {} items",
+                registry.type_name,
+                registry.items.len()
+            )?;
+            for cell in registry.cells.iter() {
+                write!(f, "    cell of {}", cell.value)?;
+                if cell.two_sided {
+                    write!(f, ", two sided")?;
+                }
+                writeln!(f)?;
+            }
+            for item in registry.items.iter() {
+                write!(f, "    item {} ", item.id)?;
+                if let Some(name) = item.name {
+                    write!(f, "{} ", name)?;
+                }
+                write!(f, "({})", item.key)?;
+                if let Some(parent) = item.parent {
+                    write!(f, ", parent {}", parent)?;
+                }
+                if item.used {
+                    write!(f, ", used")?;
+                }
+                writeln!(f)?;
+            }
+        }
+        Ok(())
+    }
+}
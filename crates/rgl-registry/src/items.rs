@@ -0,0 +1,81 @@
+use bevy::prelude::*;
+
+use crate::{Registry, RegistryId, RegistryIdFilter, RegistryIdNumeric, RegistryIdSet};
+
+/// The items of the registry, that are used by the world.
+///
+/// The ids of the items linked into the binary are the same in every world, but the item set
+/// is owned by the world like the rest of its [`RegistryScope`](crate::RegistryScope):
+/// an item becomes a member, when any data is registered for it in the app,
+/// when it is added with [`RegistryAppExt::add_registry_item`](crate::RegistryAppExt::add_registry_item)
+/// or when it is registered at runtime with
+/// [`RegistryAppExt::register_dynamic_item`](crate::RegistryAppExt::register_dynamic_item).
+///
+/// If the registry is local ([`RegistryAppExt::local_registry`](crate::RegistryAppExt::local_registry)),
+/// only the members are validated and stored in the converted data cells,
+/// otherwise every item linked into the binary is used
+#[derive(Resource)]
+pub struct RegistryItemSet<R: Registry> {
+    items: RegistryIdSet<R>,
+    local: bool,
+}
+
+impl<R: Registry> RegistryItemSet<R> {
+    /// Adds the item to the set, returns true if it wasn't a member
+    pub fn insert(&mut self, id: RegistryId<R>) -> bool {
+        self.items.insert(id)
+    }
+
+    pub fn contains(&self, id: &RegistryId<R>) -> bool {
+        self.items.contains(id)
+    }
+
+    pub fn iter(&self) -> impl Iterator<Item = RegistryId<R>> + '_ {
+        self.items.iter()
+    }
+
+    /// The amount of items used by the world, unlike [`Registry::count`]
+    pub fn len(&self) -> usize {
+        self.items.len()
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.items.is_empty()
+    }
+
+    pub fn items(&self) -> &RegistryIdSet<R> {
+        &self.items
+    }
+
+    /// Returns true if the data cells of the world are restricted to the items of the set
+    pub fn is_local(&self) -> bool {
+        self.local
+    }
+
+    pub(crate) fn set_local(&mut self) {
+        self.local = true;
+    }
+
+    /// The sorted numeric ids of the items, if the registry is local
+    pub(crate) fn local_numerics(world: &World) -> Option<Vec<RegistryIdNumeric>> {
+        world
+            .get_resource::<Self>()
+            .filter(|set| set.local)
+            .map(|set| set.iter().map(RegistryId::numeric).collect())
+    }
+}
+
+impl<R: Registry> RegistryIdFilter<R> for RegistryItemSet<R> {
+    fn contains(&self, id: &RegistryId<R>) -> bool {
+        RegistryItemSet::contains(self, id)
+    }
+}
+
+impl<R: Registry> Default for RegistryItemSet<R> {
+    fn default() -> Self {
+        Self {
+            items: RegistryIdSet::new(),
+            local: false,
+        }
+    }
+}
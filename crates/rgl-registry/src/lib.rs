@@ -1,18 +1,55 @@
 mod app;
+mod change;
+mod collections;
+mod counter;
 mod data;
+mod dynamic;
 mod id;
+mod introspection;
+mod items;
+mod manifest;
+mod multi;
+mod name;
+mod param;
+mod scope;
+mod sync;
+mod tag;
+mod validation;
 
-use std::ops::SubAssign;
+use std::marker::PhantomData;
+
+extern crate self as rgl_registry;
 
 pub use app::*;
+pub use change::*;
+pub use collections::*;
+pub use counter::*;
 pub use data::*;
+pub use dynamic::*;
 pub use id::*;
+pub use introspection::*;
+pub use items::*;
+pub use manifest::*;
+pub use multi::*;
+pub use name::*;
+pub use param::*;
+pub use rgl_registry_derive::{Registry, RegistryItem, RegistryTag};
+pub use scope::*;
+pub use sync::*;
+pub use tag::*;
+pub use validation::*;
 
 pub type RegistryIdNumeric = u16;
 
 /// The numeric representations of id must be in the order,
-/// where the first one is 0, the second is 1 and so on.   
-pub trait Registry: 'static + Sync + Send + Sized {
+/// where the first one is 0, the second is 1 and so on.
+///
+/// Ids are assigned by sorting the keys of the items (see [`RegistryIdCounter`]),
+/// so the numeric id of an item doesn't depend on the initialization order.
+///
+/// [`TypePath`](bevy::reflect::TypePath) is implemented by [`new_registry`] and the derive,
+/// it is required to reflect [`RegistryId`]
+pub trait Registry: 'static + Sync + Send + Sized + bevy::reflect::TypePath {
     type Id: 'static
         + Clone
         + Sync
@@ -25,9 +62,42 @@ pub trait Registry: 'static + Sync + Send + Sized {
 
     type IterAll: 'static + Iterator<Item = Self::Id>;
 
-    fn reserve_id() -> Self::Id;
+    #[doc(hidden)]
+    fn counter() -> &'static parking_lot::Mutex<RegistryIdCounter>;
+
+    /// The counter of the current [`RegistryScope`]
+    #[doc(hidden)]
+    fn scoped_counter() -> RegistryCounterRef {
+        scope::counter_of::<Self>()
+    }
 
-    fn count() -> usize;
+    fn reserve_id(key: &'static str) {
+        Self::counter().lock().reserve(key);
+    }
+
+    /// Reserves an id for an item, that is defined at runtime, in the current [`RegistryScope`].
+    /// Must be called before the data cells are converted, otherwise they would not contain the item.
+    /// Panics if the registry is out of ids or the data cells of the scope were already converted
+    fn reserve_dynamic_id(key: &'static str) -> Self::Id {
+        let numeric = Self::scoped_counter().lock().reserve_dynamic(key);
+        Self::Id::try_from(numeric)
+            .ok()
+            .expect("the counter checks, that the id fits")
+    }
+
+    /// Returns the id of the item with the given key.
+    /// Panics if the registry has more items, than its id type can hold
+    fn id_of(key: &str) -> Option<Self::Id> {
+        Self::scoped_counter()
+            .lock()
+            .numeric(key)
+            .and_then(|numeric| Self::Id::try_from(numeric).ok())
+    }
+
+    /// The amount of items in the current [`RegistryScope`]
+    fn count() -> usize {
+        Self::scoped_counter().lock().count()
+    }
 
     fn iter_all() -> Self::IterAll;
 }
@@ -35,6 +105,11 @@ pub trait Registry: 'static + Sync + Send + Sized {
 pub trait RegistryItem: 'static + Sync + Send + Sized {
     type Registry: Registry;
 
+    /// The key, that is used to assign the numeric id of this item.
+    /// The macros use the module path and the name of the type,
+    /// so moving or renaming the item changes the ids of the registry
+    const KEY: &'static str;
+
     fn id() -> <Self::Registry as Registry>::Id;
 }
 
@@ -42,25 +117,33 @@ pub trait ChildRegistry: Registry + RegistryItem<Registry = Registries> {}
 
 #[doc(hidden)]
 pub struct RegistryNumericIdIter<T> {
-    pub current: T,
+    current: RegistryIdNumeric,
+    count: RegistryIdNumeric,
+    _marker: PhantomData<T>,
+}
+
+impl<T> RegistryNumericIdIter<T> {
+    pub fn new(count: usize) -> Self {
+        Self {
+            current: 0,
+            count: count as RegistryIdNumeric,
+            _marker: PhantomData,
+        }
+    }
 }
 
 impl<T> Iterator for RegistryNumericIdIter<T>
 where
-    T: SubAssign<T>,
-    T: Eq,
-    T: From<u8>,
-    T: Clone,
+    T: TryFrom<RegistryIdNumeric>,
 {
     type Item = T;
 
     fn next(&mut self) -> Option<Self::Item> {
-        let to_ret = self.current.clone();
-        if to_ret == 0u8.into() {
+        if self.current == self.count {
             None
         } else {
-            self.current -= 1u8.into();
-            Some(to_ret)
+            self.current += 1;
+            T::try_from(self.current - 1).ok()
         }
     }
 }
@@ -71,9 +154,24 @@ __registry_impl!(Registries, RegistryIdNumeric);
 
 #[doc(hidden)]
 pub mod __private {
+    pub use bevy;
     pub use ctor;
     pub use parking_lot;
     pub use paste;
+
+    use crate::{ChildRegistry, Registry, RegistryItem};
+
+    pub fn add_child_registry<R: ChildRegistry>() {
+        crate::introspection::add_child_registry::<R>();
+    }
+
+    pub fn declare_parent<I, P>()
+    where
+        I: RegistryItem,
+        P: RegistryItem<Registry = I::Registry>,
+    {
+        I::Registry::counter().lock().declare_parent(I::KEY, P::KEY);
+    }
 }
 
 #[doc(hidden)]
@@ -83,23 +181,67 @@ macro_rules! __registry_item_impl {
         $crate::__private::paste::paste! {
             #[doc(hidden)]
             #[$crate::__private::ctor::ctor]
-            #[allow(non_upper_case_globals)]
-            static [<__ $item _ID>]: <$registry as $crate::Registry>::Id =
-                <$registry as $crate::Registry>::reserve_id();
+            #[allow(non_snake_case)]
+            fn [<__ $item _reserve_id>]() {
+                <$registry as $crate::Registry>::reserve_id(
+                    <$item as $crate::RegistryItem>::KEY,
+                );
+            }
         }
 
         impl $crate::RegistryItem for $item {
             type Registry = $registry;
 
+            const KEY: &'static str = concat!(module_path!(), "::", stringify!($item));
+
             fn id() -> <Self::Registry as $crate::Registry>::Id {
-                $crate::__private::paste::paste! {
-                    *[<__ $item _ID>]
-                }
+                static ID: std::sync::OnceLock<<$registry as $crate::Registry>::Id> =
+                    std::sync::OnceLock::new();
+                ID.get_or_init(|| <$registry as $crate::Registry>::id_of(Self::KEY).unwrap())
+                    .clone()
             }
         }
     };
 }
 
+#[doc(hidden)]
+#[macro_export]
+macro_rules! __registry_item_name {
+    ($item: ident, $registry: ty, $name: expr) => {
+        $crate::__private::paste::paste! {
+            #[doc(hidden)]
+            #[$crate::__private::ctor::ctor]
+            #[allow(non_snake_case)]
+            fn [<__ $item _declare_name>]() {
+                <$registry as $crate::Registry>::counter()
+                    .lock()
+                    .declare_name(<$item as $crate::RegistryItem>::KEY, $name);
+            }
+        }
+    };
+}
+
+#[doc(hidden)]
+#[macro_export]
+macro_rules! __registry_item_parent {
+    ($item: ident, $parent: ty) => {
+        $crate::__private::paste::paste! {
+            #[doc(hidden)]
+            #[$crate::__private::ctor::ctor]
+            #[allow(non_snake_case)]
+            fn [<__ $item _declare_parent>]() {
+                $crate::__private::declare_parent::<$item, $parent>();
+            }
+        }
+    };
+}
+
+/// Defines the items of the registry.
+///
+/// The keys of the items are made of [`module_path!`] and the names of the items,
+/// and the ids are assigned by sorting the keys (see [`RegistryIdCounter`]).
+/// Moving the items to another module or renaming them can change their ids,
+/// so the ids that were saved as numbers should be saved by names instead (see [`RegistryNameCell`])
 #[macro_export]
 macro_rules! new_registry_items {
     ($registry: ty {$($item: ident$(,)?)*}) => {
@@ -111,6 +253,19 @@ macro_rules! new_registry_items {
     }
 }
 
+#[macro_export]
+macro_rules! new_registry_tags {
+    ($registry: ty {$($tag: ident$(,)?)*}) => {
+        $(
+            pub struct $tag;
+
+            impl $crate::RegistryTag for $tag {
+                type Registry = $registry;
+            }
+        )*
+    }
+}
+
 #[doc(hidden)]
 #[macro_export]
 macro_rules! __registry_impl {
@@ -118,8 +273,12 @@ macro_rules! __registry_impl {
         $crate::__private::paste::paste! {
             #[doc(hidden)]
             #[allow(non_upper_case_globals)]
-            static [<__ $registry _ID_COUNTER>]: $crate::__private::parking_lot::Mutex<$id> =
-                $crate::__private::parking_lot::Mutex::new(0);
+            static [<__ $registry _ID_COUNTER>]: $crate::__private::parking_lot::Mutex<
+                $crate::RegistryIdCounter,
+            > = $crate::__private::parking_lot::Mutex::new($crate::RegistryIdCounter::new(
+                concat!(module_path!(), "::", stringify!($registry)),
+                |numeric| <$id as TryFrom<$crate::RegistryIdNumeric>>::try_from(numeric).is_ok(),
+            ));
         }
 
         impl $crate::Registry for $registry {
@@ -127,27 +286,56 @@ macro_rules! __registry_impl {
 
             type IterAll = $crate::RegistryNumericIdIter<$id>;
 
-            fn reserve_id() -> Self::Id {
+            fn counter(
+            ) -> &'static $crate::__private::parking_lot::Mutex<$crate::RegistryIdCounter> {
                 $crate::__private::paste::paste! {
-                    let mut lock = [<__ $registry _ID_COUNTER>].lock();
-                    let id = *lock;
-                    *lock += 1;
-                    id
+                    &[<__ $registry _ID_COUNTER>]
                 }
             }
 
-            fn count() -> usize {
-                $crate::__private::paste::paste! {
-                    (*[<__ $registry _ID_COUNTER>].lock()).into()
-                }
+            fn iter_all() -> Self::IterAll {
+                $crate::RegistryNumericIdIter::new(<Self as $crate::Registry>::count())
             }
+        }
 
-            fn iter_all() -> Self::IterAll {
-                Self::IterAll {
-                    current: $crate::__private::paste::paste! {
-                        (*[<__ $registry _ID_COUNTER>].lock())
-                    },
-                }
+        impl $crate::__private::bevy::reflect::TypePath for $registry {
+            fn type_path() -> &'static str {
+                concat!(module_path!(), "::", stringify!($registry))
+            }
+
+            fn short_type_path() -> &'static str {
+                stringify!($registry)
+            }
+
+            fn type_ident() -> Option<&'static str> {
+                Some(stringify!($registry))
+            }
+
+            fn crate_name() -> Option<&'static str> {
+                module_path!().split("::").next()
+            }
+
+            fn module_path() -> Option<&'static str> {
+                Some(module_path!())
+            }
+        }
+    };
+}
+
+#[doc(hidden)]
+#[macro_export]
+macro_rules! __child_registry_impl {
+    ($registry: ident) => {
+        $crate::__registry_item_impl!($registry, $crate::Registries);
+
+        impl $crate::ChildRegistry for $registry {}
+
+        $crate::__private::paste::paste! {
+            #[doc(hidden)]
+            #[$crate::__private::ctor::ctor]
+            #[allow(non_snake_case)]
+            fn [<__ $registry _add_child_registry>]() {
+                $crate::__private::add_child_registry::<$registry>();
             }
         }
     };
@@ -159,16 +347,36 @@ macro_rules! new_registry {
         pub struct $registry;
 
         $crate::__registry_impl!($registry, $id);
-        $crate::__registry_item_impl!($registry, $crate::Registries);
-
-        impl $crate::ChildRegistry for $registry {}
+        $crate::__child_registry_impl!($registry);
     };
 }
 
 #[cfg(test)]
 mod tests {
+    use std::path::Path;
 
-    use bevy::{app::App, log::LogPlugin};
+    use bevy::{
+        app::{App, PreStartup, Update},
+        asset::{
+            io::{
+                memory::{Dir, MemoryAssetReader},
+                AssetSource, AssetSourceId,
+            },
+            AssetApp, AssetPlugin, AssetServer, Assets,
+        },
+        core::TaskPoolPlugin,
+        ecs::{
+            component::Component,
+            event::{EventReader, Events},
+            reflect::{AppTypeRegistry, ReflectComponent},
+            schedule::IntoSystemConfigs,
+            system::Res,
+        },
+        log::LogPlugin,
+        reflect::{Reflect, ReflectDeserialize},
+        scene::{serde::SceneDeserializer, DynamicScene},
+    };
+    use serde::de::DeserializeSeed;
 
     use super::*;
 
@@ -179,13 +387,65 @@ mod tests {
         TestItem3,
     });
 
+    #[derive(Registry)]
+    #[registry(id = u8)]
+    pub struct DeriveTestRegistry;
+
+    /// Item with a declared name
+    #[derive(RegistryItem)]
+    #[registry(DeriveTestRegistry, name = "derived")]
+    pub struct DerivedItem;
+
+    #[derive(RegistryItem)]
+    #[registry(DeriveTestRegistry)]
+    struct UnnamedDerivedItem;
+
+    new_registry_tags!(TestRegistry { TestTag });
+
+    new_registry!(DynamicTestRegistry, u16);
+    new_registry_items!(DynamicTestRegistry { DynamicTestItem });
+
+    new_registry!(NamespaceTestRegistry, u8);
+    new_registry_items!(NamespaceTestRegistry {
+        NamespaceTestItem1,
+        NamespaceTestItem2,
+    });
+
+    #[derive(Registry)]
+    #[registry(id = u8)]
+    pub struct InheritTestRegistry;
+
+    #[derive(RegistryItem)]
+    #[registry(InheritTestRegistry, name = "floor")]
+    struct InheritFloor;
+
+    #[derive(RegistryItem)]
+    #[registry(InheritTestRegistry, name = "mossy_floor", parent = InheritFloor)]
+    struct InheritMossyFloor;
+
+    #[derive(RegistryItem)]
+    #[registry(InheritTestRegistry)]
+    struct InheritVeryMossyFloor;
+
+    #[derive(RegistryItem)]
+    #[registry(InheritTestRegistry)]
+    struct InheritWall;
+
+    new_registry_tags!(InheritTestRegistry { InheritWalkableTag });
+
+    new_registry!(LocalTestRegistry, u8);
+    new_registry_items!(LocalTestRegistry {
+        LocalTestItem1,
+        LocalTestItem2,
+    });
+
     #[test]
     fn test() {
         let mut app = App::new();
         app.add_plugins(LogPlugin::default())
-            .register_two_sided_data_id2value::<TestItem1, &'static str>("test_1")
-            .register_two_sided_data_id2value::<TestItem2, &'static str>("test_2")
-            .register_two_sided_data_id2value::<TestItem3, &'static str>("test_3")
+            .register_name::<TestItem1>("test_1")
+            .register_name::<TestItem2>("test_2")
+            .register_name::<TestItem3>("test_3")
             .register_one_sided_data::<TestItem1, u32>(1)
             .register_one_sided_data::<TestItem2, u32>(2)
             .register_one_sided_data::<TestItem3, u32>(3);
@@ -193,11 +453,11 @@ mod tests {
 
         let res = app
             .world
-            .resource::<RegistryTwoSidedDataCellId2Value<TestRegistry, &'static str>>();
+            .resource::<RegistryTwoSidedDataCellId2Value<TestRegistry, RegistryItemName>>();
 
-        assert_eq!(res.value_ty::<TestItem1>(), Some(&"test_1"));
-        assert_eq!(res.value_ty::<TestItem2>(), Some(&"test_2"));
-        assert_eq!(res.value_ty::<TestItem3>(), Some(&"test_3"));
+        assert_eq!(res.value_ty::<TestItem1>().unwrap(), "test_1");
+        assert_eq!(res.value_ty::<TestItem2>().unwrap(), "test_2");
+        assert_eq!(res.value_ty::<TestItem3>().unwrap(), "test_3");
 
         let res = app
             .world
@@ -207,4 +467,1102 @@ mod tests {
         assert_eq!(res.value_ty::<TestItem2>(), Some(&2));
         assert_eq!(res.value_ty::<TestItem3>(), Some(&3));
     }
+
+    #[test]
+    fn ids_are_sorted_by_key() {
+        assert_eq!(RegistryId::new::<TestItem1>().numeric(), 0);
+        assert_eq!(RegistryId::new::<TestItem2>().numeric(), 1);
+        assert_eq!(RegistryId::new::<TestItem3>().numeric(), 2);
+        assert_eq!(TestRegistry::count(), 3);
+        assert!(RegistryId::<TestRegistry>::iter_all().eq([
+            RegistryId::new::<TestItem1>(),
+            RegistryId::new::<TestItem2>(),
+            RegistryId::new::<TestItem3>(),
+        ]));
+    }
+
+    #[test]
+    fn serde_uses_names() {
+        let mut app = App::new();
+        app.register_name::<TestItem1>("test_1")
+            .register_name::<TestItem2>("test_2");
+
+        let scope = RegistryScope::of(&app.world);
+        let ids = vec![
+            RegistryId::new::<TestItem2>(),
+            RegistryId::new::<TestItem1>(),
+        ];
+        let serialized = scope.enter(|| ron::to_string(&ids)).unwrap();
+        assert_eq!(serialized, r#"["test_2","test_1"]"#);
+
+        let deserialized: Vec<RegistryId<TestRegistry>> =
+            scope.enter(|| ron::from_str(&serialized)).unwrap();
+        assert!(deserialized == ids);
+        assert!(scope
+            .enter(|| ron::from_str::<RegistryId<TestRegistry>>(r#""test_4""#))
+            .is_err());
+    }
+
+    #[derive(serde::Serialize, serde::Deserialize, PartialEq)]
+    struct TestUnknownIds {
+        #[serde(
+            serialize_with = "UnknownRegistryId::serialize_named::<TestRegistry, _>",
+            deserialize_with = "UnknownRegistryId::deserialize_named::<TestRegistry, _>"
+        )]
+        named: UnknownRegistryId,
+        #[serde(
+            serialize_with = "UnknownRegistryId::serialize_named::<TestRegistry, _>",
+            deserialize_with = "UnknownRegistryId::deserialize_named::<TestRegistry, _>"
+        )]
+        unnamed: UnknownRegistryId,
+        #[serde(
+            serialize_with = "UnknownRegistryId::serialize_named::<TestRegistry, _>",
+            deserialize_with = "UnknownRegistryId::deserialize_named::<TestRegistry, _>"
+        )]
+        none: UnknownRegistryId,
+    }
+
+    #[test]
+    fn unknown_id_serde_uses_names() {
+        let mut app = App::new();
+        app.register_name::<TestItem1>("test_1");
+
+        RegistryScope::of(&app.world).enter(|| {
+            let ids = TestUnknownIds {
+                named: UnknownRegistryId::new::<TestItem1>(),
+                unnamed: UnknownRegistryId::new::<TestItem3>(),
+                none: UnknownRegistryId::NONE,
+            };
+            let serialized = ron::to_string(&ids).unwrap();
+            assert_eq!(
+                serialized,
+                r#"(named:Some("test_1"),unnamed:Some(2),none:None)"#
+            );
+            assert!(ron::from_str::<TestUnknownIds>(&serialized).unwrap() == ids);
+            assert!(ron::from_str::<TestUnknownIds>(
+                r#"(named:Some("test_4"),unnamed:None,none:None)"#
+            )
+            .is_err());
+        });
+    }
+
+    #[test]
+    fn manifest_remap() {
+        let mut app = App::new();
+        app.register_name::<TestItem1>("test_1")
+            .register_name::<TestItem2>("test_2")
+            .register_name::<TestItem3>("test_3");
+
+        let scope = RegistryScope::of(&app.world);
+        let manifest = scope.enter(RegistryManifest::new::<TestRegistry>);
+        assert_eq!(manifest.entries.get("test_3"), Some(&2));
+
+        let old_manifest = RegistryManifest {
+            entries: [("test_3".to_owned(), 0), ("test_0".to_owned(), 1)].into(),
+        };
+        let remap = scope.enter(|| old_manifest.remap::<TestRegistry>());
+        assert!(remap.get(0) == Some(RegistryId::new::<TestItem3>()));
+        assert!(remap.get(1).is_none());
+        assert!(remap.get(2).is_none());
+        assert_eq!(remap.removed(), &[("test_0".to_owned(), 1)]);
+        assert!(
+            remap.added()
+                == [
+                    RegistryId::new::<TestItem1>(),
+                    RegistryId::new::<TestItem2>()
+                ]
+        );
+        assert!(!remap.is_complete());
+    }
+
+    #[test]
+    fn dynamic_items() {
+        let file = RegistryItemsFile::from_ron(r#"(items: ["lava", "ice"])"#).unwrap();
+
+        let mut app = App::new();
+        app.register_name::<DynamicTestItem>("static");
+        for name in file.items {
+            app.register_dynamic_item::<DynamicTestRegistry>(name);
+        }
+        app.update();
+
+        let scope = RegistryScope::of(&app.world);
+        assert_eq!(scope.enter(DynamicTestRegistry::count), 3);
+        let lava = scope
+            .enter(|| RegistryId::<DynamicTestRegistry>::from_name("lava"))
+            .unwrap();
+        assert_eq!(lava.numeric(), 1);
+
+        let res = app
+            .world
+            .resource::<RegistryNameCell<DynamicTestRegistry>>();
+        assert_eq!(
+            res.value(&RegistryId::new::<DynamicTestItem>()).unwrap(),
+            "static"
+        );
+        assert_eq!(res.value(&lava).unwrap(), "lava");
+        assert!(res.id_by_name("ice").unwrap().numeric() == 2);
+    }
+
+    #[test]
+    fn load_dynamic_items() {
+        let dir = Dir::default();
+        dir.insert_asset_text(Path::new("dynamic.items.ron"), r#"(items: ["mud"])"#);
+        let reader = MemoryAssetReader { root: dir };
+
+        let mut app = App::new();
+        app.register_asset_source(
+            AssetSourceId::Default,
+            AssetSource::build().with_reader(move || Box::new(reader.clone())),
+        )
+        .add_plugins((
+            TaskPoolPlugin::default(),
+            AssetPlugin::default(),
+            RegistryItemsFilePlugin,
+        ))
+        .load_dynamic_items::<DynamicTestRegistry>("dynamic.items.ron");
+        app.update();
+
+        let mud = RegistryScope::of(&app.world)
+            .enter(|| RegistryId::<DynamicTestRegistry>::from_name("mud"))
+            .unwrap();
+        assert!(app
+            .world
+            .resource::<RegistryItemSet<DynamicTestRegistry>>()
+            .contains(&mud));
+
+        let handle = app
+            .world
+            .resource::<AssetServer>()
+            .load::<RegistryItemsFile>("dynamic.items.ron");
+        for _ in 0..1000 {
+            app.update();
+            if app
+                .world
+                .resource::<Assets<RegistryItemsFile>>()
+                .contains(&handle)
+            {
+                break;
+            }
+            std::thread::sleep(std::time::Duration::from_millis(1));
+        }
+        let files = app.world.resource::<Assets<RegistryItemsFile>>();
+        assert_eq!(files.get(&handle).unwrap().items, ["mud"]);
+    }
+
+    #[test]
+    #[should_panic(expected = "can not be defined at runtime after the data cells")]
+    fn dynamic_item_after_conversion() {
+        let mut app = App::new();
+        app.register_name::<DynamicTestItem>("static");
+        app.update();
+        RegistryScope::of(&app.world)
+            .enter(|| RegistryId::<DynamicTestRegistry>::new_dynamic("late"));
+    }
+
+    #[test]
+    fn derive() {
+        assert_eq!(RegistryId::new::<DerivedItem>().name(), Some("derived"));
+
+        let mut app = App::new();
+        app.register_name::<UnnamedDerivedItem>("unnamed");
+        app.update();
+
+        let res = app.world.resource::<RegistryNameCell<DeriveTestRegistry>>();
+        assert_eq!(res.value_ty::<DerivedItem>().unwrap(), "derived");
+        assert_eq!(res.value_ty::<UnnamedDerivedItem>().unwrap(), "unnamed");
+    }
+
+    #[test]
+    fn validation() {
+        let mut app = App::new();
+        app.register_name::<TestItem2>("test_2")
+            .register_one_sided_data::<TestItem1, i64>(1);
+        app.update();
+
+        let validation = app.world.resource::<RegistryValidation>();
+        assert!(validation.result().is_err());
+        assert_eq!(validation.missing.len(), 2);
+        assert!(validation.missing.iter().any(|entry| entry.id == 1
+            && entry.name == Some("test_2")
+            && entry.value == std::any::type_name::<i64>()
+            && entry.registry == std::any::type_name::<TestRegistry>()));
+        let skipped = std::any::type_name::<RegistryOneSidedDataCell<TestRegistry, i64>>();
+        assert!(validation.skipped.contains(&skipped));
+
+        assert!(!app
+            .world
+            .contains_resource::<ChangableRegistryOneSidedDataCell<TestRegistry, i64>>());
+        assert!(!app
+            .world
+            .contains_resource::<RegistryOneSidedDataCell<TestRegistry, i64>>());
+    }
+
+    #[test]
+    #[should_panic(expected = "registry entries are missing")]
+    fn validation_panics() {
+        let mut app = App::new();
+        app.panic_on_invalid_registries()
+            .register_one_sided_data::<TestItem1, i64>(1);
+        app.update();
+    }
+
+    #[test]
+    fn default_values() {
+        let mut app = App::new();
+        app.register_one_sided_data::<TestItem1, i32>(5)
+            .register_one_sided_data_default::<TestRegistry, i32>(7)
+            .init_one_sided_data_default::<TestRegistry, u64>();
+        app.update();
+
+        let res = app
+            .world
+            .resource::<RegistryOneSidedDataCell<TestRegistry, i32>>();
+        assert_eq!(res.value_ty::<TestItem1>(), Some(&5));
+        assert_eq!(res.value_ty::<TestItem2>(), Some(&7));
+        assert_eq!(res.value_ty::<TestItem3>(), Some(&7));
+
+        let res = app
+            .world
+            .resource::<RegistryOneSidedDataCell<TestRegistry, u64>>();
+        assert_eq!(res.c1.iter().copied().collect::<Vec<_>>(), vec![0; 3]);
+    }
+
+    #[test]
+    fn tags() {
+        let mut app = App::new();
+        app.add_to_tag::<TestTag, TestItem3>()
+            .add_to_tag::<TestTag, TestItem1>();
+        app.update();
+
+        let tag = app.world.resource::<RegistryTagSet<TestTag>>();
+        assert!(tag.contains(&RegistryId::new::<TestItem1>()));
+        assert!(!tag.contains(&RegistryId::new::<TestItem2>()));
+        assert!(tag.iter().eq([
+            RegistryId::new::<TestItem1>(),
+            RegistryId::new::<TestItem3>()
+        ]));
+        assert_eq!(tag.len(), 2);
+    }
+
+    #[test]
+    fn collections() {
+        let first = [
+            RegistryId::new::<TestItem1>(),
+            RegistryId::new::<TestItem2>(),
+        ]
+        .into_iter()
+        .collect::<RegistryIdSet<TestRegistry>>();
+        let second = [
+            RegistryId::new::<TestItem2>(),
+            RegistryId::new::<TestItem3>(),
+        ]
+        .into_iter()
+        .collect::<RegistryIdSet<TestRegistry>>();
+
+        assert!(first
+            .intersection(&second)
+            .iter()
+            .eq([RegistryId::new::<TestItem2>()]));
+        assert_eq!(first.union(&second), RegistryIdSet::full());
+        assert!(first.symmetric_difference(&second).iter().eq([
+            RegistryId::new::<TestItem1>(),
+            RegistryId::new::<TestItem3>()
+        ]));
+        assert!(first.difference(&second).is_subset(&first));
+        assert!(!first.is_disjoint(&second));
+
+        let mut map = RegistryIdMap::<TestRegistry, u32>::default();
+        map.insert(RegistryId::new::<TestItem1>(), 1);
+        map.insert(RegistryId::new::<TestItem3>(), 3);
+        assert_eq!(
+            RegistryIdSet::from(&map),
+            first.symmetric_difference(&second)
+        );
+        let err = RegistryIdArray::try_from(map).unwrap_err();
+        assert_eq!(err.missing.len(), 1);
+
+        let mut set = RegistryIdSet::<TestRegistry>::new();
+        assert!(set.is_empty());
+        set.insert(RegistryId::new::<TestItem3>());
+        set.clear();
+        assert!(set.is_empty());
+        assert_eq!(set, RegistryIdSet::new());
+        assert_eq!(
+            RegistryIdArray::<TestRegistry, u32>::new().len(),
+            TestRegistry::count()
+        );
+
+        let mut array = RegistryIdArray::<TestRegistry, u32>::from_fn(|id| id.numeric().into());
+        array[&RegistryId::new::<TestItem3>()] = 5;
+        assert_eq!(array.values().copied().collect::<Vec<_>>(), vec![0, 1, 5]);
+        assert!(array.filter(|v| *v > 0).iter().eq([
+            RegistryId::new::<TestItem2>(),
+            RegistryId::new::<TestItem3>()
+        ]));
+    }
+
+    #[test]
+    fn system_param() {
+        fn check(
+            names: RegistryData<TestRegistry, RegistryItemName>,
+            mut values: RegistryDataMut<TestRegistry, u16>,
+            missing: RegistryData<TestRegistry, i8>,
+        ) {
+            assert!(!missing.is_registered());
+            assert!(names.is_converted());
+            assert_eq!(
+                names.id(&RegistryItemName::new("test_2")),
+                Some(RegistryId::new::<TestItem2>())
+            );
+            assert_eq!(names.value_ty::<TestItem3>().unwrap(), "test_3");
+
+            assert!(!values.is_converted());
+            *values.value_mut_ty::<TestItem1>().unwrap() += 1;
+            values.insert(RegistryId::new::<TestItem2>(), 5).unwrap();
+            assert_eq!(
+                values
+                    .iter()
+                    .map(|(id, v)| (id.numeric(), *v))
+                    .collect::<Vec<_>>(),
+                vec![(0, 2), (1, 5)]
+            );
+        }
+
+        let mut app = App::new();
+        app.register_name::<TestItem1>("test_1")
+            .register_name::<TestItem2>("test_2")
+            .register_name::<TestItem3>("test_3")
+            .register_one_sided_data::<TestItem1, u16>(1)
+            .keep_changable_one_sided_data::<TestRegistry, u16>()
+            .add_systems(Update, check);
+        app.update();
+    }
+
+    #[test]
+    fn change_events() {
+        fn changes(app: &mut App) -> Vec<(RegistryIdNumeric, RegistryDataChangeKind)> {
+            let events = app
+                .world
+                .resource::<Events<RegistryDataChanged<TestRegistry, i16>>>();
+            events
+                .get_reader()
+                .read(events)
+                .map(|event| (event.id.numeric(), event.kind))
+                .collect()
+        }
+
+        let mut app = App::new();
+        app.register_one_sided_data::<TestItem2, i16>(2)
+            .keep_changable_one_sided_data::<TestRegistry, i16>();
+        app.update();
+        assert_eq!(changes(&mut app), [(1, RegistryDataChangeKind::Inserted)]);
+
+        let mut cell = app
+            .world
+            .resource_mut::<ChangableRegistryOneSidedDataCell<TestRegistry, i16>>();
+        cell.insert_one_sided(RegistryId::new::<TestItem3>(), 3);
+        *cell.value_mut(&RegistryId::new::<TestItem3>()).unwrap() += 1;
+        *cell.value_mut(&RegistryId::new::<TestItem2>()).unwrap() += 1;
+        app.update();
+        app.update();
+        assert_eq!(
+            changes(&mut app),
+            [
+                (1, RegistryDataChangeKind::Overwritten),
+                (2, RegistryDataChangeKind::Inserted)
+            ]
+        );
+    }
+
+    #[test]
+    fn two_sided_change_events() {
+        let mut app = App::new();
+        app.register_two_sided_data_id2value::<TestItem1, char>('a')
+            .register_two_sided_data_id2value::<TestItem2, char>('b')
+            .keep_changable_two_sided_data_id2value::<TestRegistry, char>();
+        app.update();
+        app.update();
+
+        let mut cell = app
+            .world
+            .resource_mut::<ChangableRegistryTwoSidedDataCellId2Value<TestRegistry, char>>();
+        assert_eq!(
+            cell.insert(RegistryId::new::<TestItem2>(), 'a'),
+            (Some('b'), Some(RegistryId::new::<TestItem1>()))
+        );
+        assert_eq!(cell.value_ty::<TestItem1>(), None);
+        assert_eq!(cell.id(&'a'), Some(&RegistryId::new::<TestItem2>()));
+        assert_eq!(cell.id(&'b'), None);
+        app.update();
+        app.update();
+
+        let events = app
+            .world
+            .resource::<Events<RegistryDataChanged<TestRegistry, char>>>();
+        assert_eq!(
+            events
+                .get_reader()
+                .read(events)
+                .map(|event| (event.id.numeric(), event.kind))
+                .collect::<Vec<_>>(),
+            [
+                (0, RegistryDataChangeKind::Removed),
+                (1, RegistryDataChangeKind::Overwritten)
+            ]
+        );
+    }
+
+    #[test]
+    fn frozen_insert() {
+        fn check(mut chars: RegistryDataMut<TestRegistry, char>) {
+            let err = chars
+                .insert(RegistryId::new::<TestItem2>(), 'a')
+                .unwrap_err();
+            assert!(err.to_string().contains("is frozen"));
+            assert_eq!(chars.value_ty::<TestItem1>(), Some(&'a'));
+            assert_eq!(chars.value_ty::<TestItem2>(), Some(&'b'));
+            assert_eq!(chars.id(&'a'), Some(RegistryId::new::<TestItem1>()));
+        }
+
+        let mut app = App::new();
+        app.register_two_sided_data_id2value::<TestItem1, char>('a')
+            .register_two_sided_data_id2value::<TestItem2, char>('b')
+            .register_two_sided_data_id2value::<TestItem3, char>('c')
+            .add_systems(Update, check);
+        app.update();
+    }
+
+    #[test]
+    #[should_panic(expected = "is frozen")]
+    fn frozen_value_mut() {
+        fn check(mut values: RegistryDataMut<TestRegistry, u16>) {
+            values.value_mut_ty::<TestItem1>();
+        }
+
+        let mut app = App::new();
+        app.register_one_sided_data::<TestItem1, u16>(1)
+            .register_one_sided_data::<TestItem2, u16>(2)
+            .register_one_sided_data::<TestItem3, u16>(3)
+            .add_systems(Update, check);
+        app.update();
+    }
+
+    #[test]
+    fn vec_data() {
+        let mut app = App::new();
+        app.register_one_sided_vec_data::<TestItem1, u64>(1)
+            .register_one_sided_vec_data::<TestItem1, u64>(2)
+            .register_one_sided_vec_data::<TestItem3, u64>(3);
+        app.update();
+
+        let res = app
+            .world
+            .resource::<RegistryOneSidedDataCell<TestRegistry, Vec<u64>>>();
+        assert_eq!(res.value_ty::<TestItem1>().unwrap(), &[1, 2]);
+        assert!(res.value_ty::<TestItem2>().unwrap().is_empty());
+        assert_eq!(res.value_ty::<TestItem3>().unwrap(), &[3]);
+        assert!(!app
+            .world
+            .contains_resource::<ChangableRegistryOneSidedDataCell<TestRegistry, Vec<u64>>>());
+    }
+
+    #[test]
+    fn many_to_many() {
+        let mut app = App::new();
+        app.register_many_to_many_data::<TestItem1, char>('a')
+            .register_many_to_many_data::<TestItem1, char>('b')
+            .register_many_to_many_data::<TestItem3, char>('a')
+            .register_many_to_many_data::<TestItem3, char>('a');
+        app.update();
+
+        let res = app
+            .world
+            .resource::<RegistryManyToManyDataCell<TestRegistry, char>>();
+        assert_eq!(res.values_of_ty::<TestItem1>(), &['a', 'b']);
+        assert!(res.values_of_ty::<TestItem2>().is_empty());
+        assert_eq!(res.values_of_ty::<TestItem3>(), &['a']);
+        assert!(
+            res.ids_of(&'a')
+                == [
+                    RegistryId::new::<TestItem1>(),
+                    RegistryId::new::<TestItem3>()
+                ]
+        );
+        assert!(res.ids_of(&'c').is_empty());
+    }
+
+    #[test]
+    fn many_to_many_change_events() {
+        let mut app = App::new();
+        app.register_many_to_many_data::<TestItem1, u8>(1)
+            .register_many_to_many_data::<TestItem1, u8>(2)
+            .register_many_to_many_data::<TestItem2, u8>(1)
+            .keep_changable_many_to_many_data::<TestRegistry, u8>();
+        app.update();
+        app.update();
+
+        let mut cell = app
+            .world
+            .resource_mut::<ChangableRegistryManyToManyDataCell<TestRegistry, u8>>();
+        assert!(cell.remove(&RegistryId::new::<TestItem1>(), &2));
+        assert!(cell.remove(&RegistryId::new::<TestItem2>(), &1));
+        assert!(cell.insert_pair(RegistryId::new::<TestItem3>(), 1));
+        assert!(!cell.insert_pair(RegistryId::new::<TestItem1>(), 1));
+        app.update();
+        app.update();
+
+        let events = app
+            .world
+            .resource::<Events<RegistryDataChanged<TestRegistry, u8>>>();
+        assert_eq!(
+            events
+                .get_reader()
+                .read(events)
+                .map(|event| (event.id.numeric(), event.kind))
+                .collect::<Vec<_>>(),
+            [
+                (0, RegistryDataChangeKind::Overwritten),
+                (1, RegistryDataChangeKind::Removed),
+                (2, RegistryDataChangeKind::Inserted)
+            ]
+        );
+    }
+
+    #[test]
+    fn local_registries() {
+        let mut first = App::new();
+        first
+            .local_registry::<LocalTestRegistry>()
+            .register_one_sided_data::<LocalTestItem1, u32>(1)
+            .init_one_sided_data_default::<LocalTestRegistry, u32>()
+            .register_name::<LocalTestItem1>("local_1")
+            .register_dynamic_item::<LocalTestRegistry>("local");
+        first.update();
+
+        let mut second = App::new();
+        second
+            .local_registry::<LocalTestRegistry>()
+            .add_registry_item::<LocalTestItem2>()
+            .register_one_sided_data::<LocalTestItem2, u32>(2)
+            .init_one_sided_data_default::<LocalTestRegistry, u32>()
+            .register_name::<LocalTestItem2>("local_2")
+            .register_dynamic_item::<LocalTestRegistry>("local");
+        second.update();
+
+        // Every app has its own names and dynamic items
+        let local_of =
+            |app: &App| RegistryScope::of(&app.world).enter(|| RegistryId::from_name("local"));
+        let local = local_of(&first).unwrap();
+        assert!(local_of(&second) == Some(local));
+        assert!(RegistryScope::of(&second.world)
+            .enter(|| RegistryId::<LocalTestRegistry>::from_name("local_1"))
+            .is_none());
+        // Without an entered scope only the declared items are known, no matter how many apps are alive
+        assert!(RegistryId::<LocalTestRegistry>::from_name("local").is_none());
+        for app in [&first, &second] {
+            assert!(app.world.resource::<RegistryValidation>().is_valid());
+            let items = app.world.resource::<RegistryItemSet<LocalTestRegistry>>();
+            assert_eq!(items.len(), 2);
+            assert!(items.contains(&local));
+            assert!(app
+                .world
+                .resource::<RegistryNameCell<LocalTestRegistry>>()
+                .id_by_name("local")
+                .is_some_and(|id| *id == local));
+        }
+
+        let res = first
+            .world
+            .resource::<RegistryOneSidedDataCell<LocalTestRegistry, u32>>();
+        assert_eq!(res.value_ty::<LocalTestItem1>(), Some(&1));
+        assert_eq!(res.value_ty::<LocalTestItem2>(), None);
+        assert!(res
+            .c1
+            .entries()
+            .eq([(RegistryId::new::<LocalTestItem1>(), &1), (local, &0)]));
+
+        let res = second
+            .world
+            .resource::<RegistryOneSidedDataCell<LocalTestRegistry, u32>>();
+        assert_eq!(res.value_ty::<LocalTestItem1>(), None);
+        assert_eq!(res.value_ty::<LocalTestItem2>(), Some(&2));
+
+        drop(first);
+        assert!(RegistryId::<LocalTestRegistry>::from_name("local_2").is_none());
+    }
+
+    #[test]
+    fn namespaced_names() {
+        let mut app = App::new();
+        app.register_name::<NamespaceTestItem1>("mypack:lava")
+            .register_name::<NamespaceTestItem2>("floor");
+        app.update();
+
+        RegistryScope::of(&app.world).enter(|| {
+            let lava = RegistryId::<NamespaceTestRegistry>::new::<NamespaceTestItem1>();
+            let floor = RegistryId::<NamespaceTestRegistry>::new::<NamespaceTestItem2>();
+            assert!(RegistryId::from_name("core:floor") == Some(floor));
+            assert!(RegistryId::from_name("floor") == Some(floor));
+            assert!(RegistryId::from_name("mypack:lava") == Some(lava));
+            assert!(RegistryId::<NamespaceTestRegistry>::from_name("lava").is_none());
+
+            let res = app
+                .world
+                .resource::<RegistryNameCell<NamespaceTestRegistry>>();
+            assert!(res.id_by_name("core:floor") == Some(&floor));
+            assert!(res.id_by_name("mypack:lava") == Some(&lava));
+            assert!(res.id_by_name("core:lava").is_none());
+        });
+    }
+
+    #[test]
+    #[should_panic(expected = "Registry name mypack:lava is registered for both")]
+    fn name_collision() {
+        let mut app = App::new();
+        app.register_name::<NamespaceTestItem1>("mypack:lava")
+            .register_name::<NamespaceTestItem2>("mypack:lava");
+    }
+
+    #[test]
+    fn name_collision_sources() {
+        let mut app = App::new();
+        let first_line = line!() + 1;
+        app.register_name::<NamespaceTestItem1>("mypack:water");
+        let second_line = line!() + 2;
+        let result = std::panic::catch_unwind(std::panic::AssertUnwindSafe(|| {
+            app.register_name::<NamespaceTestItem2>("mypack:water");
+        }));
+        let message = *result.unwrap_err().downcast::<String>().unwrap();
+        assert!(message.contains(&format!(
+            "{} (registered at {}:{}",
+            NamespaceTestItem1::KEY,
+            file!(),
+            first_line
+        )));
+        assert!(message.contains(&format!(
+            "{} (registered at {}:{}",
+            NamespaceTestItem2::KEY,
+            file!(),
+            second_line
+        )));
+    }
+
+    #[test]
+    fn names_per_app() {
+        let mut first = App::new();
+        first.register_name::<NamespaceTestItem1>("mypack:ice");
+        let mut second = App::new();
+        second.register_name::<NamespaceTestItem2>("mypack:ice");
+
+        for (app, item) in [
+            (&first, RegistryId::new::<NamespaceTestItem1>()),
+            (&second, RegistryId::new::<NamespaceTestItem2>()),
+        ] {
+            assert!(
+                RegistryScope::of(&app.world)
+                    .enter(|| RegistryId::<NamespaceTestRegistry>::from_name("mypack:ice"))
+                    == Some(item)
+            );
+        }
+    }
+
+    #[test]
+    #[should_panic(expected = "Value of two sided data char is registered for both")]
+    fn two_sided_value_collision() {
+        let mut app = App::new();
+        app.register_two_sided_data_id2value::<TestItem1, char>('x')
+            .register_two_sided_data_id2value::<TestItem2, char>('x');
+    }
+
+    #[test]
+    fn inheritance() {
+        let mut app = App::new();
+        app.register_item_parent::<InheritVeryMossyFloor, InheritMossyFloor>()
+            .inherit_one_sided_data::<InheritTestRegistry, u32>()
+            .register_one_sided_data::<InheritFloor, u32>(1)
+            .register_one_sided_data::<InheritVeryMossyFloor, u32>(2)
+            .register_one_sided_data::<InheritWall, u32>(3)
+            .add_to_tag::<InheritWalkableTag, InheritFloor>();
+        app.update();
+
+        let res = app
+            .world
+            .resource::<RegistryOneSidedDataCell<InheritTestRegistry, u32>>();
+        assert_eq!(res.value_ty::<InheritFloor>(), Some(&1));
+        assert_eq!(res.value_ty::<InheritMossyFloor>(), Some(&1));
+        assert_eq!(res.value_ty::<InheritVeryMossyFloor>(), Some(&2));
+        assert_eq!(res.value_ty::<InheritWall>(), Some(&3));
+
+        RegistryScope::of(&app.world).enter(|| {
+            let floor = RegistryId::<InheritTestRegistry>::new::<InheritFloor>();
+            let mossy_floor = RegistryId::new::<InheritMossyFloor>();
+            let very_mossy_floor = RegistryId::new::<InheritVeryMossyFloor>();
+            let wall = RegistryId::new::<InheritWall>();
+            assert_eq!(mossy_floor.parent(), Some(floor));
+            assert_eq!(floor.parent(), None);
+            assert!(very_mossy_floor.is_a(&mossy_floor));
+            assert!(very_mossy_floor.is_a_ty::<InheritFloor>());
+            assert!(floor.is_a(&floor));
+            assert!(!floor.is_a(&mossy_floor));
+            assert!(!wall.is_a(&floor));
+
+            let filter = RegistryIsA(floor);
+            assert!(filter.contains(&very_mossy_floor));
+            assert!(!filter.contains(&wall));
+
+            let walkable = app.world.resource::<RegistryTagSet<InheritWalkableTag>>();
+            assert!(!walkable.contains(&mossy_floor));
+            assert!(walkable.contains_inherited(&very_mossy_floor));
+            assert!(!walkable.contains_inherited(&wall));
+        });
+    }
+
+    #[test]
+    #[should_panic(expected = "it would be its own ancestor")]
+    fn inheritance_cycle() {
+        App::new().register_item_parent::<InheritFloor, InheritMossyFloor>();
+    }
+
+    #[derive(Component)]
+    struct TestLastHurtBy(AnyRegistryId);
+
+    #[test]
+    fn any_registry_id() {
+        let mut app = App::new();
+        app.register_name::<TestRegistry>("test")
+            .register_name::<TestItem1>("test_1")
+            .register_name::<TestItem2>("test_2")
+            .register_name::<TestItem3>("mod:test_3")
+            .register_type::<AnyRegistryId>();
+        app.update();
+
+        RegistryScope::of(&app.world).enter(|| {
+            let id = AnyRegistryId::from(RegistryId::<TestRegistry>::new::<TestItem2>());
+            assert_eq!(id, AnyRegistryId::new::<TestItem2>());
+            assert!(id.is::<TestItem2>());
+            assert!(!id.is::<TestItem1>());
+            assert!(!id.is::<DerivedItem>());
+            assert_eq!(
+                id.downcast::<TestRegistry>(),
+                Some(RegistryId::new::<TestItem2>())
+            );
+            assert_eq!(id.downcast::<DeriveTestRegistry>(), None);
+            assert_eq!(id.name(), Some("test_2"));
+            assert_eq!(id.to_string(), r#""test":"test_2""#);
+            assert_eq!(
+                AnyRegistryId::new::<DerivedItem>().to_string(),
+                format!(r#""{}":"derived""#, DeriveTestRegistry::KEY)
+            );
+            // The namespace of the item is not mistaken for the registry
+            assert_eq!(
+                AnyRegistryId::new::<TestItem3>().to_string(),
+                r#""test":"mod:test_3""#
+            );
+
+            let entity = app.world.spawn(TestLastHurtBy(id)).id();
+            let hurt_by = app.world.get::<TestLastHurtBy>(entity).unwrap().0;
+            assert_eq!(
+                hurt_by.downcast::<TestRegistry>().unwrap().name(),
+                Some("test_2")
+            );
+
+            let ron = ron::to_string(&id).unwrap();
+            assert_eq!(ron, "(\"test\",\"test_2\")");
+            assert_eq!(ron::from_str::<AnyRegistryId>(&ron).unwrap(), id);
+            let derived = AnyRegistryId::new::<DerivedItem>();
+            let ron = ron::to_string(&derived).unwrap();
+            assert_eq!(ron::from_str::<AnyRegistryId>(&ron).unwrap(), derived);
+            assert!(ron::from_str::<AnyRegistryId>("(\"test\",\"derived\")").is_err());
+
+            let type_registry = app.world.resource::<AppTypeRegistry>().read();
+            let reflect_deserialize = type_registry
+                .get_type_data::<ReflectDeserialize>(std::any::TypeId::of::<AnyRegistryId>())
+                .unwrap();
+            let mut deserializer = ron::Deserializer::from_str("(\"test\",\"test_1\")").unwrap();
+            let reflected = reflect_deserialize.deserialize(&mut deserializer).unwrap();
+            assert_eq!(
+                reflected.downcast_ref::<AnyRegistryId>(),
+                Some(&AnyRegistryId::new::<TestItem1>())
+            );
+        });
+    }
+
+    #[test]
+    fn dump() {
+        let mut app = App::new();
+        app.register_name::<TestItem1>("test_1")
+            .register_name::<TestItem2>("test_2")
+            .register_name::<TestItem3>("test_3")
+            .register_one_sided_data::<TestItem1, u32>(1)
+            .register_one_sided_data::<TestItem2, u32>(2)
+            .register_one_sided_data::<TestItem3, u32>(3);
+        app.update();
+
+        let dump = RegistryDump::new(&app.world);
+        assert!(dump.get(DeriveTestRegistry::KEY).is_some());
+        let info = dump.get(TestRegistry::KEY).unwrap();
+        assert_eq!(
+            info.id,
+            RegistryId::<Registries>::new::<TestRegistry>().numeric()
+        );
+        assert_eq!(info.items.len(), 3);
+        assert_eq!(info.items[1].name, Some("test_2"));
+        assert_eq!(info.items[1].key, TestItem2::KEY);
+        assert!(info.items.iter().all(|item| item.used));
+        let mut values = info.cells.iter().map(|cell| cell.value).collect::<Vec<_>>();
+        values.sort_unstable();
+        assert_eq!(values, [std::any::type_name::<RegistryItemName>(), "u32"]);
+        assert!(info
+            .cells
+            .iter()
+            .any(|cell| cell.value == std::any::type_name::<RegistryItemName>() && cell.two_sided));
+
+        assert!(dump.to_string().contains("item 1 test_2"));
+        let json = serde_json::from_str::<serde_json::Value>(&dump.to_json()).unwrap();
+        assert!(json["registries"]
+            .as_array()
+            .unwrap()
+            .iter()
+            .any(|registry| registry["key"] == TestRegistry::KEY));
+    }
+
+    #[test]
+    fn sync_handshake() {
+        let mut app = App::new();
+        app.register_name::<TestItem1>("test_1")
+            .register_name::<TestItem2>("test_2")
+            .register_name::<TestItem3>("test_3");
+        app.update();
+
+        RegistryScope::of(&app.world).enter(|| {
+            let mut manifest = RegistrySyncManifest::new();
+            let test_manifest = manifest
+                .registries
+                .get_mut(<TestRegistry as RegistryItem>::KEY)
+                .unwrap();
+            assert_eq!(test_manifest.names.get("test_2"), Some(&1));
+            // The server has the items in the other order
+            test_manifest.names = [("test_1", 2), ("test_2", 0), ("test_3", 1)]
+                .into_iter()
+                .map(|(name, id)| (name.to_owned(), id))
+                .collect();
+            // Items without a name are synchronized by their keys
+            let local_manifest = manifest
+                .registries
+                .get_mut(<LocalTestRegistry as RegistryItem>::KEY)
+                .unwrap();
+            assert!(local_manifest.names.is_empty());
+            assert_eq!(
+                local_manifest
+                    .keys
+                    .get(<LocalTestItem1 as RegistryItem>::KEY),
+                Some(&0)
+            );
+            local_manifest.keys = [
+                (<LocalTestItem1 as RegistryItem>::KEY, 1),
+                (<LocalTestItem2 as RegistryItem>::KEY, 0),
+            ]
+            .into_iter()
+            .map(|(key, id)| (key.to_owned(), id))
+            .collect();
+
+            let mut to_client = vec![];
+            manifest.send(&mut to_client).unwrap();
+            let manifest = RegistrySyncManifest::receive(to_client.as_slice()).unwrap();
+            let sync = manifest.sync();
+            let mut to_server = vec![];
+            RegistrySyncResponse::new(&sync)
+                .send(&mut to_server)
+                .unwrap();
+            assert_eq!(
+                RegistrySyncResponse::receive(to_server.as_slice()).unwrap(),
+                RegistrySyncResponse::Accepted
+            );
+
+            let sync = sync.unwrap();
+            assert_eq!(
+                sync.to_local::<TestRegistry>(0),
+                Some(RegistryId::new::<TestItem2>())
+            );
+            assert_eq!(
+                sync.to_remote(RegistryId::<TestRegistry>::new::<TestItem1>()),
+                Some(2)
+            );
+            assert_eq!(sync.to_local::<TestRegistry>(3), None);
+            assert_eq!(
+                sync.to_local::<LocalTestRegistry>(0),
+                Some(RegistryId::new::<LocalTestItem2>())
+            );
+            assert_eq!(
+                sync.to_remote(RegistryId::<LocalTestRegistry>::new::<LocalTestItem1>()),
+                Some(1)
+            );
+
+            let mut manifest = manifest;
+            manifest
+                .registries
+                .get_mut(<TestRegistry as RegistryItem>::KEY)
+                .unwrap()
+                .names
+                .insert("test_9".to_owned(), 3);
+            manifest
+                .registries
+                .get_mut(<TestRegistry as RegistryItem>::KEY)
+                .unwrap()
+                .names
+                .remove("test_3");
+            let inherit_manifest = manifest
+                .registries
+                .get_mut(<InheritTestRegistry as RegistryItem>::KEY)
+                .unwrap();
+            inherit_manifest.keys.insert("unknown::Item".to_owned(), 9);
+            inherit_manifest
+                .keys
+                .remove(<InheritWall as RegistryItem>::KEY)
+                .unwrap();
+            manifest.registries.insert(
+                "unknown::Registry".to_owned(),
+                RegistrySyncEntries::default(),
+            );
+            manifest
+                .registries
+                .remove(<LocalTestRegistry as RegistryItem>::KEY);
+            let err = manifest.sync().unwrap_err();
+            assert_eq!(
+                err.mismatches,
+                [
+                    RegistrySyncMismatch::MissingItem {
+                        registry: <InheritTestRegistry as RegistryItem>::KEY.to_owned(),
+                        name: "unknown::Item".to_owned(),
+                    },
+                    RegistrySyncMismatch::UnknownItem {
+                        registry: <InheritTestRegistry as RegistryItem>::KEY.to_owned(),
+                        name: <InheritWall as RegistryItem>::KEY.to_owned(),
+                    },
+                    RegistrySyncMismatch::MissingItem {
+                        registry: <TestRegistry as RegistryItem>::KEY.to_owned(),
+                        name: "test_9".to_owned(),
+                    },
+                    RegistrySyncMismatch::UnknownItem {
+                        registry: <TestRegistry as RegistryItem>::KEY.to_owned(),
+                        name: "test_3".to_owned(),
+                    },
+                    RegistrySyncMismatch::MissingRegistry {
+                        registry: "unknown::Registry".to_owned(),
+                    },
+                    RegistrySyncMismatch::UnknownRegistry {
+                        registry: <LocalTestRegistry as RegistryItem>::KEY.to_owned(),
+                    },
+                ]
+            );
+            assert!(matches!(
+                RegistrySyncResponse::new(&Err(err)),
+                RegistrySyncResponse::Rejected(mismatches) if mismatches.len() == 6
+            ));
+        });
+    }
+
+    #[test]
+    fn phases() {
+        fn check(
+            phase: Res<RegistryPhase>,
+            mut frozen: EventReader<RegistryFrozen>,
+            names: Res<RegistryNameCell<TestRegistry>>,
+        ) {
+            assert_eq!(*phase, RegistryPhase::Frozen);
+            assert_eq!(frozen.read().count(), 1);
+            assert_eq!(names.value_ty::<TestItem1>().unwrap(), "test_1");
+        }
+
+        let mut app = App::new();
+        app.register_name::<TestItem1>("test_1")
+            .register_name::<TestItem2>("test_2")
+            .register_name::<TestItem3>("test_3")
+            .add_systems(PreStartup, check.after(RegistrySet));
+        assert_eq!(
+            *app.world.resource::<RegistryPhase>(),
+            RegistryPhase::Registering
+        );
+        app.update();
+    }
+
+    #[test]
+    #[should_panic(expected = "can not be registered after the registries were frozen")]
+    fn register_after_freeze() {
+        let mut app = App::new();
+        app.register_one_sided_data::<TestItem1, u32>(1)
+            .register_one_sided_data_default::<TestRegistry, u32>(0);
+        app.update();
+        app.register_one_sided_data::<TestItem2, u32>(2);
+    }
+
+    #[test]
+    #[should_panic(expected = "Registry test::Small has 3 items")]
+    fn counter_overflow() {
+        let mut counter = RegistryIdCounter::new("test::Small", |numeric| numeric < 2);
+        counter.reserve("test::A");
+        counter.reserve("test::B");
+        counter.reserve("test::C");
+        counter.freeze();
+    }
+
+    #[test]
+    #[should_panic(expected = "Registry test::Small has 3 items")]
+    fn dynamic_overflow() {
+        let mut counter = RegistryIdCounter::new("test::Small", |numeric| numeric < 2);
+        counter.reserve("test::A");
+        counter.reserve("test::B");
+        counter.reserve_dynamic("test::C");
+    }
+
+    #[derive(Component, Reflect, Default)]
+    #[reflect(Component)]
+    struct TestTiles {
+        tiles: Vec<RegistryId<TestRegistry>>,
+    }
+
+    #[test]
+    fn scene_uses_names() {
+        let mut app = App::new();
+        app.register_name::<TestItem1>("test_1")
+            .register_name::<TestItem2>("test_2")
+            .register_name::<TestItem3>("test_3")
+            .register_type::<TestTiles>();
+        app.update();
+
+        let tiles = vec![
+            RegistryId::new::<TestItem2>(),
+            RegistryId::new::<TestItem3>(),
+        ];
+        let entity = app
+            .world
+            .spawn(TestTiles {
+                tiles: tiles.clone(),
+            })
+            .id();
+
+        RegistryScope::of(&app.world).enter(|| {
+            let type_registry = app.world.resource::<AppTypeRegistry>().clone();
+            let ron = DynamicScene::from_world(&app.world)
+                .serialize_ron(&type_registry)
+                .unwrap();
+            assert!(ron.contains("\"test_2\""));
+            assert!(ron.contains("\"test_3\""));
+
+            let mut deserializer = ron::Deserializer::from_str(&ron).unwrap();
+            let scene = SceneDeserializer {
+                type_registry: &type_registry.read(),
+            }
+            .deserialize(&mut deserializer)
+            .unwrap();
+            app.world.despawn(entity);
+            scene
+                .write_to_world(&mut app.world, &mut Default::default())
+                .unwrap();
+
+            let mut query = app.world.query::<&TestTiles>();
+            assert_eq!(query.single(&app.world).tiles, tiles);
+        });
+    }
 }
@@ -0,0 +1,84 @@
+use std::collections::BTreeMap;
+
+use serde::{Deserialize, Serialize};
+
+use crate::{Registry, RegistryId, RegistryIdNumeric};
+
+/// Names and numeric ids of the items of a registry.
+/// Should be saved alongside the data, that contains [`RegistryIdNumeric`],
+/// so the data can be loaded with [`RegistryRemap`] after the ids were changed.
+///
+/// Only items with a name in the [`RegistryNameCell`](crate::RegistryNameCell) are included
+#[derive(Serialize, Deserialize, Clone, Debug, Default, PartialEq, Eq)]
+pub struct RegistryManifest {
+    pub entries: BTreeMap<String, RegistryIdNumeric>,
+}
+
+impl RegistryManifest {
+    /// Creates a manifest of the current ids of the registry
+    pub fn new<R: Registry>() -> Self {
+        Self {
+            entries: RegistryId::<R>::iter_all()
+                .filter_map(|id| Some((id.name()?.to_owned(), id.numeric())))
+                .collect(),
+        }
+    }
+
+    /// Creates a table, that translates ids of this manifest to the current ids of the registry
+    pub fn remap<R: Registry>(&self) -> RegistryRemap<R> {
+        let mut remap = RegistryRemap {
+            table: vec![],
+            removed: vec![],
+            added: vec![],
+        };
+
+        for (name, &old_id) in self.entries.iter() {
+            match RegistryId::<R>::from_name(name) {
+                Some(id) => {
+                    let index = old_id as usize;
+                    if index >= remap.table.len() {
+                        remap.table.resize_with(index + 1, || None);
+                    }
+                    remap.table[index] = Some(id);
+                }
+                None => remap.removed.push((name.clone(), old_id)),
+            }
+        }
+
+        remap.added = RegistryId::<R>::iter_all()
+            .filter(|id| !matches!(id.name(), Some(name) if self.entries.contains_key(name)))
+            .collect();
+
+        remap
+    }
+}
+
+/// Translates ids of an old [`RegistryManifest`] to the current ids of the registry
+pub struct RegistryRemap<R: Registry> {
+    table: Vec<Option<RegistryId<R>>>,
+    removed: Vec<(String, RegistryIdNumeric)>,
+    added: Vec<RegistryId<R>>,
+}
+
+impl<R: Registry> RegistryRemap<R> {
+    /// Returns the current id of the old one,
+    /// [`None`] if the item was removed or the id is unknown for the manifest
+    pub fn get(&self, old_id: RegistryIdNumeric) -> Option<RegistryId<R>> {
+        self.table.get(old_id as usize).cloned().flatten()
+    }
+
+    /// Names and old ids of the items, that are in the manifest, but not in the registry
+    pub fn removed(&self) -> &[(String, RegistryIdNumeric)] {
+        &self.removed
+    }
+
+    /// Items of the registry, that are not in the manifest
+    pub fn added(&self) -> &[RegistryId<R>] {
+        &self.added
+    }
+
+    /// Returns true if every item of the manifest is still in the registry
+    pub fn is_complete(&self) -> bool {
+        self.removed.is_empty()
+    }
+}
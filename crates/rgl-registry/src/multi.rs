@@ -0,0 +1,300 @@
+use std::{
+    collections::BTreeMap,
+    hash::{BuildHasher, Hash},
+    marker::PhantomData,
+};
+
+use bevy::utils::hashbrown::HashMap;
+
+use crate::{
+    change::record_change, Registry, RegistryDataCell, RegistryDataChangeKind, RegistryId,
+    RegistryIdNumeric, RegistryItem, RegistryMapChanges, RegistryMapConvert, RegistryMapInsert,
+};
+
+/// Map with any amount of values under one key
+pub trait RegistryMultiMapGet<K, V> {
+    fn get_all(&self, key: &K) -> &[V];
+}
+
+/// Map from registry id to any amount of values.
+/// [`RegistryMapInsert::insert`] adds the value to the values of the id,
+/// if the same value is already there, it is returned back
+pub struct RegistryIdMultiMap<R: Registry, V> {
+    objects: Vec<Vec<V>>,
+    changes: BTreeMap<RegistryIdNumeric, RegistryDataChangeKind>,
+    _marker: PhantomData<R>,
+}
+
+impl<R: Registry, V: PartialEq> RegistryMapInsert<RegistryId<R>, V> for RegistryIdMultiMap<R, V> {
+    fn insert(&mut self, key: RegistryId<R>, value: V) -> Option<V> {
+        let index = key.numeric() as usize;
+        if index >= self.objects.len() {
+            self.objects.resize_with(index + 1, Vec::new);
+        }
+        let values = &mut self.objects[index];
+        let kind = if values.is_empty() {
+            RegistryDataChangeKind::Inserted
+        } else {
+            RegistryDataChangeKind::Overwritten
+        };
+        let old = push_unique(values, value);
+        if old.is_none() {
+            record_change(&mut self.changes, index as RegistryIdNumeric, kind);
+        }
+        old
+    }
+}
+
+/// The id is inserted, when it gets its first value and removed, when it loses its last value,
+/// other changes of the values are overwrites
+impl<R: Registry, V> RegistryMapChanges<R> for RegistryIdMultiMap<R, V> {
+    fn take_changes(&mut self) -> Vec<(RegistryId<R>, RegistryDataChangeKind)> {
+        std::mem::take(&mut self.changes)
+            .into_iter()
+            .map(|(id, kind)| (RegistryId::from_numeric_unchecked(id), kind))
+            .collect()
+    }
+}
+
+impl<R: Registry, V> RegistryMultiMapGet<RegistryId<R>, V> for RegistryIdMultiMap<R, V> {
+    fn get_all(&self, key: &RegistryId<R>) -> &[V] {
+        self.objects
+            .get(key.clone().numeric() as usize)
+            .map_or(&[], Vec::as_slice)
+    }
+}
+
+impl<R: Registry, V> RegistryMapConvert for RegistryIdMultiMap<R, V> {
+    type Converted = ConvertedRegistryIdMultiMap<R, V>;
+
+    /// Every item without values just has no values, so nothing is missing
+    fn convert(self) -> Self::Converted {
+        let mut offsets = Vec::with_capacity(R::count() + 1);
+        let mut values = Vec::with_capacity(self.objects.iter().map(Vec::len).sum());
+        let mut objects = self.objects;
+        objects.resize_with(R::count(), Vec::new);
+        offsets.push(0);
+        for object in objects {
+            values.extend(object);
+            offsets.push(values.len());
+        }
+        ConvertedRegistryIdMultiMap {
+            values,
+            offsets,
+            _marker: PhantomData,
+        }
+    }
+}
+
+impl<R: Registry, V> RegistryIdMultiMap<R, V> {
+    /// Removes the value from the values of the id, returns it if it was there
+    pub fn remove(&mut self, key: &RegistryId<R>, value: &V) -> Option<V>
+    where
+        V: PartialEq,
+    {
+        let numeric = key.clone().numeric();
+        let values = self.objects.get_mut(numeric as usize)?;
+        let index = values.iter().position(|it| it == value)?;
+        let value = values.swap_remove(index);
+        let kind = if values.is_empty() {
+            RegistryDataChangeKind::Removed
+        } else {
+            RegistryDataChangeKind::Overwritten
+        };
+        record_change(&mut self.changes, numeric, kind);
+        Some(value)
+    }
+
+    /// Iterates over the ids and their values
+    pub fn entries(&self) -> impl Iterator<Item = (RegistryId<R>, &[V])> + '_ {
+        self.objects.iter().enumerate().map(|(i, values)| {
+            (
+                RegistryId::from_numeric_unchecked(i as RegistryIdNumeric),
+                values.as_slice(),
+            )
+        })
+    }
+}
+
+impl<R: Registry, V> Default for RegistryIdMultiMap<R, V> {
+    fn default() -> Self {
+        Self {
+            objects: vec![],
+            changes: BTreeMap::new(),
+            _marker: PhantomData,
+        }
+    }
+}
+
+/// The values of every item are stored in one [`Vec`]
+#[doc(hidden)]
+pub struct ConvertedRegistryIdMultiMap<R: Registry, V> {
+    values: Vec<V>,
+    offsets: Vec<usize>,
+    _marker: PhantomData<R>,
+}
+
+impl<R: Registry, V> RegistryMultiMapGet<RegistryId<R>, V> for ConvertedRegistryIdMultiMap<R, V> {
+    fn get_all(&self, key: &RegistryId<R>) -> &[V] {
+        let index = key.clone().numeric() as usize;
+        match (self.offsets.get(index), self.offsets.get(index + 1)) {
+            (Some(&start), Some(&end)) => &self.values[start..end],
+            _ => &[],
+        }
+    }
+}
+
+impl<R: Registry, V> ConvertedRegistryIdMultiMap<R, V> {
+    /// Iterates over the ids and their values
+    pub fn entries(&self) -> impl Iterator<Item = (RegistryId<R>, &[V])> + '_ {
+        self.offsets.windows(2).enumerate().map(|(i, range)| {
+            (
+                RegistryId::from_numeric_unchecked(i as RegistryIdNumeric),
+                &self.values[range[0]..range[1]],
+            )
+        })
+    }
+}
+
+/// Map from hashable key to any amount of values, see [`RegistryIdMultiMap`]
+pub struct RegistryHashMultiMap<K, V, S = bevy::utils::hashbrown::hash_map::DefaultHashBuilder> {
+    objects: HashMap<K, Vec<V>, S>,
+}
+
+impl<K, V, S> RegistryMapInsert<K, V> for RegistryHashMultiMap<K, V, S>
+where
+    K: Eq + Hash,
+    V: PartialEq,
+    S: BuildHasher,
+{
+    fn insert(&mut self, key: K, value: V) -> Option<V> {
+        push_unique(self.objects.entry(key).or_default(), value)
+    }
+}
+
+impl<K, V, S> RegistryMultiMapGet<K, V> for RegistryHashMultiMap<K, V, S>
+where
+    K: Eq + Hash,
+    S: BuildHasher,
+{
+    fn get_all(&self, key: &K) -> &[V] {
+        self.objects.get(key).map_or(&[], Vec::as_slice)
+    }
+}
+
+impl<K, V, S> RegistryMapConvert for RegistryHashMultiMap<K, V, S> {
+    type Converted = Self;
+
+    fn convert(self) -> Self::Converted {
+        self
+    }
+}
+
+impl<K, V, S> RegistryHashMultiMap<K, V, S>
+where
+    K: Eq + Hash,
+    S: BuildHasher,
+{
+    /// Removes the value from the values of the key, returns it if it was there
+    pub fn remove(&mut self, key: &K, value: &V) -> Option<V>
+    where
+        V: PartialEq,
+    {
+        let values = self.objects.get_mut(key)?;
+        let index = values.iter().position(|it| it == value)?;
+        let value = values.swap_remove(index);
+        if values.is_empty() {
+            self.objects.remove(key);
+        }
+        Some(value)
+    }
+
+    pub fn keys(&self) -> impl Iterator<Item = &K> + '_ {
+        self.objects.keys()
+    }
+}
+
+impl<K, V, S: Default> Default for RegistryHashMultiMap<K, V, S> {
+    fn default() -> Self {
+        Self {
+            objects: HashMap::default(),
+        }
+    }
+}
+
+fn push_unique<V: PartialEq>(values: &mut Vec<V>, value: V) -> Option<V> {
+    if values.contains(&value) {
+        Some(value)
+    } else {
+        values.push(value);
+        None
+    }
+}
+
+/// A table, where every id can have many values and every value can have many ids
+pub type RegistryManyToManyDataCell<R, V> = RegistryDataCell<
+    RegistryId<R>,
+    V,
+    <RegistryIdMultiMap<R, V> as RegistryMapConvert>::Converted,
+    <RegistryHashMultiMap<V, RegistryId<R>> as RegistryMapConvert>::Converted,
+>;
+
+/// The same as [`RegistryManyToManyDataCell`] with concepts of
+/// [`ChangableRegistryTwoSidedDataCellId2Value`](crate::ChangableRegistryTwoSidedDataCellId2Value)
+pub type ChangableRegistryManyToManyDataCell<R, V> = RegistryDataCell<
+    RegistryId<R>,
+    V,
+    RegistryIdMultiMap<R, V>,
+    RegistryHashMultiMap<V, RegistryId<R>>,
+>;
+
+impl<Id, Value, C1, C2> RegistryDataCell<Id, Value, C1, C2> {
+    /// Returns every value of the id
+    pub fn values_of(&self, id: &Id) -> &[Value]
+    where
+        C1: RegistryMultiMapGet<Id, Value>,
+    {
+        self.c1.get_all(id)
+    }
+
+    /// Returns every id of the value
+    pub fn ids_of(&self, value: &Value) -> &[Id]
+    where
+        C2: RegistryMultiMapGet<Value, Id>,
+    {
+        self.c2.get_all(value)
+    }
+}
+
+impl<R, Value, C1, C2> RegistryDataCell<RegistryId<R>, Value, C1, C2>
+where
+    R: Registry,
+{
+    /// Returns every value of the [`RegistryItem`]
+    pub fn values_of_ty<I>(&self) -> &[Value]
+    where
+        I: RegistryItem<Registry = R>,
+        C1: RegistryMultiMapGet<RegistryId<R>, Value>,
+    {
+        self.c1.get_all(&RegistryId::new::<I>())
+    }
+}
+
+impl<R, Value> ChangableRegistryManyToManyDataCell<R, Value>
+where
+    R: Registry,
+    Value: Hash + Eq + Clone,
+{
+    /// Adds the pair, returns false if it already existed
+    pub fn insert_pair(&mut self, id: RegistryId<R>, value: Value) -> bool {
+        self.c2.insert(value.clone(), id.clone());
+        self.c1.insert(id, value).is_none()
+    }
+
+    /// Removes the pair, returns true if it existed
+    pub fn remove(&mut self, id: &RegistryId<R>, value: &Value) -> bool {
+        let removed = self.c1.remove(id, value).is_some();
+        self.c2.remove(value, id);
+        removed
+    }
+}
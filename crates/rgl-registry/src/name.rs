@@ -0,0 +1,165 @@
+use std::{borrow::Borrow, fmt, hash::BuildHasher, ops::Deref, panic::Location};
+
+use bevy::utils::hashbrown::HashMap;
+
+use crate::{Registry, RegistryDataCell, RegistryId};
+
+/// A name of a registry item, that consists of a namespace and a path: `core:floor`, `mypack:lava`.
+///
+/// Names without a namespace (short names) are in the [`RegistryName::DEFAULT_NAMESPACE`],
+/// so `floor` and `core:floor` are the same name.
+/// Content packs and plugins should use their own namespace, so their names don't collide
+#[derive(Clone, Copy, Debug, PartialEq, Eq, Hash, PartialOrd, Ord)]
+pub struct RegistryName<'a> {
+    pub namespace: &'a str,
+    pub path: &'a str,
+}
+
+impl<'a> RegistryName<'a> {
+    pub const DEFAULT_NAMESPACE: &'static str = "core";
+
+    pub fn new(namespace: &'a str, path: &'a str) -> Self {
+        Self { namespace, path }
+    }
+
+    /// Splits the name at the first `:`, the name without it is in the default namespace
+    pub fn parse(name: &'a str) -> Self {
+        match name.split_once(':') {
+            Some((namespace, path)) => Self::new(namespace, path),
+            None => Self::new(Self::DEFAULT_NAMESPACE, name),
+        }
+    }
+
+    pub fn is_default_namespace(&self) -> bool {
+        self.namespace == Self::DEFAULT_NAMESPACE
+    }
+}
+
+/// Formats the full name
+impl fmt::Display for RegistryName<'_> {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(f, "{}:{}", self.namespace, self.path)
+    }
+}
+
+/// A name of a registry item, that is stored in the [`RegistryNameCell`](crate::RegistryNameCell),
+/// see [`RegistryAppExt::register_name`](crate::RegistryAppExt::register_name).
+/// The cell can be searched with `&str`, see [`RegistryDataCell::id_by_name`]
+#[derive(Clone, Copy, Debug, PartialEq, Eq, Hash, PartialOrd, Ord)]
+pub struct RegistryItemName(&'static str);
+
+impl RegistryItemName {
+    pub const fn new(name: &'static str) -> Self {
+        Self(name)
+    }
+
+    pub const fn as_str(&self) -> &'static str {
+        self.0
+    }
+}
+
+impl Deref for RegistryItemName {
+    type Target = str;
+
+    fn deref(&self) -> &Self::Target {
+        self.0
+    }
+}
+
+impl Borrow<str> for RegistryItemName {
+    fn borrow(&self) -> &str {
+        self.0
+    }
+}
+
+impl PartialEq<str> for RegistryItemName {
+    fn eq(&self, other: &str) -> bool {
+        self.0 == other
+    }
+}
+
+impl PartialEq<&str> for RegistryItemName {
+    fn eq(&self, other: &&str) -> bool {
+        self.0 == *other
+    }
+}
+
+impl fmt::Display for RegistryItemName {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.write_str(self.0)
+    }
+}
+
+/// The item, that got a name, and the place, where the name was registered
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub struct RegistryNameSource {
+    /// Key of the item
+    pub key: Option<&'static str>,
+    /// The call of [`RegistryAppExt::register_name`](crate::RegistryAppExt::register_name)
+    /// or [`RegistryAppExt::register_dynamic_item`](crate::RegistryAppExt::register_dynamic_item),
+    /// `None` if the name is declared with `#[registry(...)]`
+    pub location: Option<&'static Location<'static>>,
+}
+
+impl fmt::Display for RegistryNameSource {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.write_str(self.key.unwrap_or("<unknown>"))?;
+        match self.location {
+            Some(location) => write!(f, " (registered at {})", location),
+            None => f.write_str(" (declared)"),
+        }
+    }
+}
+
+/// The same name is registered for 2 different items
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub struct RegistryNameCollision {
+    pub name: &'static str,
+    /// Name of the registry
+    pub registry: &'static str,
+    /// The item, that had the name first
+    pub first: RegistryNameSource,
+    /// The item, that tried to get the name
+    pub second: RegistryNameSource,
+}
+
+impl fmt::Display for RegistryNameCollision {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(
+            f,
+            "Registry name {} is registered for both {} and {} in registry {}",
+            RegistryName::parse(self.name),
+            self.first,
+            self.second,
+            self.registry
+        )
+    }
+}
+
+impl std::error::Error for RegistryNameCollision {}
+
+impl<R, C1, S>
+    RegistryDataCell<
+        RegistryId<R>,
+        RegistryItemName,
+        C1,
+        HashMap<RegistryItemName, RegistryId<R>, S>,
+    >
+where
+    R: Registry,
+    S: BuildHasher,
+{
+    /// Returns id using the full or the short name, see [`RegistryName`]
+    pub fn id_by_name(&self, name: &str) -> Option<&RegistryId<R>> {
+        self.c2.get(name).or_else(|| {
+            let name = RegistryName::parse(name);
+            if name.is_default_namespace() {
+                self.c2
+                    .get(name.path)
+                    .or_else(|| self.c2.get(name.to_string().as_str()))
+            } else {
+                None
+            }
+        })
+    }
+}
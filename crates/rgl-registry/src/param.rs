@@ -0,0 +1,296 @@
+use std::{any::type_name, fmt, hash::Hash, marker::PhantomData};
+
+use bevy::{ecs::system::SystemParam, prelude::*, utils::HashMap};
+
+use crate::{
+    ChangableRegistryOneSidedDataCell, ChangableRegistryTwoSidedDataCellId2Value,
+    ConvertedRegistryIdMap, Registry, RegistryId, RegistryIdMap, RegistryItem, RegistryMapGet,
+    RegistryMapGetMut, RegistryOneSidedDataCell, RegistryTwoSidedDataCellId2Value,
+};
+
+fn frozen<R, V>() -> ! {
+    panic!("{}", RegistryDataFrozenError::<R, V>::new())
+}
+
+fn not_registered<R, V>() -> ! {
+    panic!(
+        "Registry data cell with value {} was never registered for registry {}",
+        type_name::<V>(),
+        type_name::<R>()
+    )
+}
+
+/// Returned by [`RegistryDataMut::insert`] and [`RegistryDataMut::insert_one_sided`],
+/// if the data cell was converted, so it can't be written, see [`RegistryDataRead::is_converted`]
+pub struct RegistryDataFrozenError<R, V> {
+    _marker: PhantomData<fn() -> (R, V)>,
+}
+
+impl<R, V> RegistryDataFrozenError<R, V> {
+    fn new() -> Self {
+        Self {
+            _marker: PhantomData,
+        }
+    }
+}
+
+impl<R, V> fmt::Debug for RegistryDataFrozenError<R, V> {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(
+            f,
+            "RegistryDataFrozenError<{}, {}>",
+            type_name::<R>(),
+            type_name::<V>()
+        )
+    }
+}
+
+impl<R, V> fmt::Display for RegistryDataFrozenError<R, V> {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(
+            f,
+            "Data cell {} of registry {} is frozen, keep it changable to insert values",
+            type_name::<V>(),
+            type_name::<R>()
+        )
+    }
+}
+
+impl<R, V> std::error::Error for RegistryDataFrozenError<R, V> {}
+
+/// The old value and the displaced id returned by [`RegistryDataMut::insert`]
+pub type RegistryDataInsertResult<R, V> =
+    Result<(Option<V>, Option<RegistryId<R>>), RegistryDataFrozenError<R, V>>;
+
+enum DataRef<'a, R: Registry, V> {
+    Converted(&'a ConvertedRegistryIdMap<R, V>),
+    Changable(&'a RegistryIdMap<R, V>),
+}
+
+impl<'a, R: Registry, V> DataRef<'a, R, V> {
+    fn value(&self, id: &RegistryId<R>) -> Option<&'a V> {
+        match self {
+            Self::Converted(map) => map.get(id),
+            Self::Changable(map) => map.get(id),
+        }
+    }
+
+    fn iter(&self) -> Box<dyn Iterator<Item = (RegistryId<R>, &'a V)> + 'a> {
+        match self {
+            Self::Converted(map) => Box::new(map.entries()),
+            Self::Changable(map) => Box::new(map.entries()),
+        }
+    }
+}
+
+/// The data cells borrowed by [`RegistryData`] or [`RegistryDataMut`], see [`RegistryDataRead`]
+#[doc(hidden)]
+pub struct RegistryDataCells<'a, R: Registry, V> {
+    one_sided: Option<&'a RegistryOneSidedDataCell<R, V>>,
+    changable_one_sided: Option<&'a ChangableRegistryOneSidedDataCell<R, V>>,
+    two_sided: Option<&'a RegistryTwoSidedDataCellId2Value<R, V>>,
+    changable_two_sided: Option<&'a ChangableRegistryTwoSidedDataCellId2Value<R, V>>,
+}
+
+impl<'a, R: Registry, V> RegistryDataCells<'a, R, V> {
+    fn data(&self) -> DataRef<'a, R, V> {
+        if let Some(cell) = self.one_sided {
+            DataRef::Converted(&cell.c1)
+        } else if let Some(cell) = self.changable_one_sided {
+            DataRef::Changable(&cell.c1)
+        } else if let Some(cell) = self.two_sided {
+            DataRef::Converted(&cell.c1)
+        } else if let Some(cell) = self.changable_two_sided {
+            DataRef::Changable(&cell.c1)
+        } else {
+            not_registered::<R, V>()
+        }
+    }
+
+    fn reverse(&self) -> Option<&'a HashMap<V, RegistryId<R>>> {
+        if let Some(cell) = self.two_sided {
+            Some(&cell.c2)
+        } else {
+            self.changable_two_sided.map(|cell| &cell.c2)
+        }
+    }
+}
+
+/// Read access shared by [`RegistryData`] and [`RegistryDataMut`]
+pub trait RegistryDataRead<R: Registry, V: Sync + Send + 'static> {
+    #[doc(hidden)]
+    fn cells(&self) -> RegistryDataCells<'_, R, V>;
+
+    /// Returns true if the data cell exists
+    fn is_registered(&self) -> bool {
+        let cells = self.cells();
+        cells.one_sided.is_some()
+            || cells.changable_one_sided.is_some()
+            || cells.two_sided.is_some()
+            || cells.changable_two_sided.is_some()
+    }
+
+    /// Returns true if the data cell was converted, so every item has a value
+    fn is_converted(&self) -> bool {
+        let cells = self.cells();
+        cells.one_sided.is_some() || cells.two_sided.is_some()
+    }
+
+    /// Returns value using given id
+    fn value(&self, id: &RegistryId<R>) -> Option<&V> {
+        self.cells().data().value(id)
+    }
+
+    /// Returns value using given [`RegistryItem`]
+    fn value_ty<I>(&self) -> Option<&V>
+    where
+        I: RegistryItem<Registry = R>,
+    {
+        self.value(&RegistryId::new::<I>())
+    }
+
+    /// Returns id using given value, always [`None`] for one sided data cells
+    fn id(&self, value: &V) -> Option<RegistryId<R>>
+    where
+        V: Hash + Eq,
+    {
+        self.cells().reverse()?.get(value).cloned()
+    }
+
+    /// Iterates over the ids and their values
+    fn iter(&self) -> Box<dyn Iterator<Item = (RegistryId<R>, &V)> + '_> {
+        self.cells().data().iter()
+    }
+}
+
+/// Read access to a one sided or two sided (id to value) data cell,
+/// that works the same way before and after the cell is converted and for changable cells.
+/// Panics on access if the cell was never registered, see [`RegistryDataRead::is_registered`]
+#[derive(SystemParam)]
+pub struct RegistryData<'w, R: Registry, V: Sync + Send + 'static> {
+    one_sided: Option<Res<'w, RegistryOneSidedDataCell<R, V>>>,
+    changable_one_sided: Option<Res<'w, ChangableRegistryOneSidedDataCell<R, V>>>,
+    two_sided: Option<Res<'w, RegistryTwoSidedDataCellId2Value<R, V>>>,
+    changable_two_sided: Option<Res<'w, ChangableRegistryTwoSidedDataCellId2Value<R, V>>>,
+}
+
+impl<'w, R: Registry, V: Sync + Send + 'static> RegistryDataRead<R, V> for RegistryData<'w, R, V> {
+    fn cells(&self) -> RegistryDataCells<'_, R, V> {
+        RegistryDataCells {
+            one_sided: self.one_sided.as_deref(),
+            changable_one_sided: self.changable_one_sided.as_deref(),
+            two_sided: self.two_sided.as_deref(),
+            changable_two_sided: self.changable_two_sided.as_deref(),
+        }
+    }
+}
+
+/// Mutable version of [`RegistryData`].
+/// Only changable data cells can be written, so every write is tracked, see [`RegistryDataChanged`](crate::RegistryDataChanged).
+/// Converted data cells are only borrowed immutably: inserting into them fails
+/// and mutating their values panics, keep the cell changable with one of the `keep_changable_*` methods
+/// of [`RegistryAppExt`](crate::RegistryAppExt) to write it at runtime
+#[derive(SystemParam)]
+pub struct RegistryDataMut<'w, R: Registry, V: Sync + Send + 'static> {
+    one_sided: Option<Res<'w, RegistryOneSidedDataCell<R, V>>>,
+    changable_one_sided: Option<ResMut<'w, ChangableRegistryOneSidedDataCell<R, V>>>,
+    two_sided: Option<Res<'w, RegistryTwoSidedDataCellId2Value<R, V>>>,
+    changable_two_sided: Option<ResMut<'w, ChangableRegistryTwoSidedDataCellId2Value<R, V>>>,
+}
+
+impl<'w, R: Registry, V: Sync + Send + 'static> RegistryDataRead<R, V>
+    for RegistryDataMut<'w, R, V>
+{
+    fn cells(&self) -> RegistryDataCells<'_, R, V> {
+        RegistryDataCells {
+            one_sided: self.one_sided.as_deref(),
+            changable_one_sided: self.changable_one_sided.as_deref(),
+            two_sided: self.two_sided.as_deref(),
+            changable_two_sided: self.changable_two_sided.as_deref(),
+        }
+    }
+}
+
+impl<'w, R: Registry, V: Sync + Send + 'static> RegistryDataMut<'w, R, V> {
+    /// Returns mutable reference to the value under given id.
+    /// Panics if the data cell was converted or is two sided, because the value is also a key,
+    /// use [`RegistryDataMut::insert`]
+    pub fn value_mut(&mut self, id: &RegistryId<R>) -> Option<&mut V> {
+        if self.one_sided.is_some() {
+            frozen::<R, V>()
+        } else if let Some(cell) = &mut self.changable_one_sided {
+            cell.c1.get_mut(id)
+        } else if self.two_sided.is_some() || self.changable_two_sided.is_some() {
+            panic!(
+                "Values of two sided data cell {} of registry {} can not be mutated, they must be inserted",
+                type_name::<V>(),
+                type_name::<R>()
+            )
+        } else {
+            not_registered::<R, V>()
+        }
+    }
+
+    /// Returns mutable reference to the value under given [`RegistryItem`], see [`RegistryDataMut::value_mut`]
+    pub fn value_mut_ty<I>(&mut self) -> Option<&mut V>
+    where
+        I: RegistryItem<Registry = R>,
+    {
+        self.value_mut(&RegistryId::new::<I>())
+    }
+
+    /// Iterates over the ids and mutable references to their values, see [`RegistryDataMut::value_mut`]
+    pub fn iter_mut(&mut self) -> Box<dyn Iterator<Item = (RegistryId<R>, &mut V)> + '_> {
+        if self.one_sided.is_some() {
+            frozen::<R, V>()
+        } else if let Some(cell) = &mut self.changable_one_sided {
+            Box::new(cell.c1.entries_mut())
+        } else if self.two_sided.is_some() || self.changable_two_sided.is_some() {
+            panic!(
+                "Values of two sided data cell {} of registry {} can not be mutated, they must be inserted",
+                type_name::<V>(),
+                type_name::<R>()
+            )
+        } else {
+            not_registered::<R, V>()
+        }
+    }
+
+    /// Inserts new entry to a changable one sided data cell, returns the old value.
+    /// Fails if the data cell was converted, panics if the data cell is two sided
+    pub fn insert_one_sided(
+        &mut self,
+        id: RegistryId<R>,
+        value: V,
+    ) -> Result<Option<V>, RegistryDataFrozenError<R, V>> {
+        if self.one_sided.is_some() {
+            Err(RegistryDataFrozenError::new())
+        } else if let Some(cell) = &mut self.changable_one_sided {
+            Ok(cell.insert_one_sided(id, value))
+        } else if self.two_sided.is_some() || self.changable_two_sided.is_some() {
+            panic!(
+                "Data cell {} of registry {} is two sided, use RegistryDataMut::insert",
+                type_name::<V>(),
+                type_name::<R>()
+            )
+        } else {
+            not_registered::<R, V>()
+        }
+    }
+
+    /// Inserts new entry to a changable data cell, returns the old value and the other id,
+    /// that had the value before and lost it, the other id is always [`None`] for one sided data cells.
+    /// Fails if the data cell was converted
+    pub fn insert(&mut self, id: RegistryId<R>, value: V) -> RegistryDataInsertResult<R, V>
+    where
+        V: Hash + Eq + Clone,
+    {
+        if self.two_sided.is_some() {
+            Err(RegistryDataFrozenError::new())
+        } else if let Some(cell) = &mut self.changable_two_sided {
+            Ok(cell.insert(id, value))
+        } else {
+            Ok((self.insert_one_sided(id, value)?, None))
+        }
+    }
+}
@@ -0,0 +1,139 @@
+use std::{
+    any::TypeId,
+    cell::RefCell,
+    sync::{
+        atomic::{AtomicBool, Ordering},
+        Arc,
+    },
+};
+
+use bevy::{ecs::world::World, prelude::Resource, utils::HashMap};
+use parking_lot::{Mutex, MutexGuard, RwLock};
+
+use crate::{Registry, RegistryIdCounter};
+
+#[derive(Default)]
+struct ScopeState {
+    counters: RwLock<HashMap<TypeId, Arc<Mutex<RegistryIdCounter>>>>,
+    sealed: AtomicBool,
+}
+
+thread_local! {
+    static ENTERED: RefCell<Vec<RegistryScope>> = const { RefCell::new(Vec::new()) };
+}
+
+/// The registry state owned by one app: the items defined at runtime,
+/// the names, the parents of the items and so the name collisions.
+///
+/// Every registry starts with a copy of the ids of the items linked into the binary
+/// and the names and parents declared with `#[registry(...)]`, so the ids of those items are the same
+/// in every app, but everything registered by the app stays in its scope.
+///
+/// The lookups without access to the world ([`RegistryId::name`](crate::RegistryId::name),
+/// [`RegistryId::from_name`](crate::RegistryId::from_name), serialization, [`Registry::count`] and so on)
+/// use the scope entered with [`RegistryScope::enter`] on the current thread.
+/// If no scope is entered, only the items linked into the binary and the declared names and parents
+/// are known, no matter how many apps are alive
+#[derive(Resource, Clone)]
+pub struct RegistryScope {
+    state: Arc<ScopeState>,
+}
+
+impl RegistryScope {
+    pub fn new() -> Self {
+        Self {
+            state: Arc::default(),
+        }
+    }
+
+    /// Returns the scope of the world, panics if the world has no registries
+    pub fn of(world: &World) -> Self {
+        match world.get_resource::<Self>() {
+            Some(scope) => scope.clone(),
+            None => panic!("The world has no RegistryScope, no registries were added to its app"),
+        }
+    }
+
+    /// Runs the function with this scope entered on the current thread
+    pub fn enter<T>(&self, f: impl FnOnce() -> T) -> T {
+        struct Exit;
+
+        impl Drop for Exit {
+            fn drop(&mut self) {
+                ENTERED.with(|entered| entered.borrow_mut().pop());
+            }
+        }
+
+        ENTERED.with(|entered| entered.borrow_mut().push(self.clone()));
+        let _exit = Exit;
+        f()
+    }
+
+    /// Returns the counter of the registry in this scope
+    #[doc(hidden)]
+    pub fn counter<R: Registry>(&self) -> Arc<Mutex<RegistryIdCounter>> {
+        if let Some(counter) = self.state.counters.read().get(&TypeId::of::<R>()) {
+            return counter.clone();
+        }
+        self.state
+            .counters
+            .write()
+            .entry(TypeId::of::<R>())
+            .or_insert_with(|| {
+                let mut counter = R::counter().lock().scoped();
+                if self.is_sealed() {
+                    counter.seal();
+                }
+                Arc::new(Mutex::new(counter))
+            })
+            .clone()
+    }
+
+    /// Forbids defining items at runtime, is called when the data cells of the app are converted
+    pub fn seal(&self) {
+        let counters = self.state.counters.read();
+        self.state.sealed.store(true, Ordering::Release);
+        for counter in counters.values() {
+            counter.lock().seal();
+        }
+    }
+
+    /// Returns true if the items can not be defined at runtime anymore
+    pub fn is_sealed(&self) -> bool {
+        self.state.sealed.load(Ordering::Acquire)
+    }
+
+    /// The scope entered on the current thread
+    fn current() -> Option<Self> {
+        ENTERED.with(|entered| entered.borrow().last().cloned())
+    }
+}
+
+impl Default for RegistryScope {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+/// The counter of a registry in the current [`RegistryScope`] or the counter shared by the process
+#[doc(hidden)]
+pub enum RegistryCounterRef {
+    Shared(&'static Mutex<RegistryIdCounter>),
+    Scoped(Arc<Mutex<RegistryIdCounter>>),
+}
+
+impl RegistryCounterRef {
+    pub fn lock(&self) -> MutexGuard<'_, RegistryIdCounter> {
+        match self {
+            Self::Shared(counter) => counter.lock(),
+            Self::Scoped(counter) => counter.lock(),
+        }
+    }
+}
+
+pub(crate) fn counter_of<R: Registry>() -> RegistryCounterRef {
+    match RegistryScope::current() {
+        Some(scope) => RegistryCounterRef::Scoped(scope.counter::<R>()),
+        None => RegistryCounterRef::Shared(R::counter()),
+    }
+}
@@ -0,0 +1,265 @@
+use std::{
+    collections::BTreeMap,
+    fmt,
+    io::{self, BufRead, Write},
+};
+
+use bevy::ecs::system::Resource;
+use serde::{Deserialize, Serialize};
+
+use crate::{
+    introspection::child_registries, ChildRegistry, RegistryId, RegistryIdNumeric, RegistryItem,
+};
+
+/// The ids of every child registry of the server, keyed by the keys of the registries.
+///
+/// The handshake: the server sends its manifest with [`RegistrySyncManifest::send`],
+/// the client receives it with [`RegistrySyncManifest::receive`], builds the translation tables
+/// with [`RegistrySyncManifest::sync`] and answers with [`RegistrySyncResponse`].
+/// Named items are synchronized by their names, the other items by their keys
+#[derive(Serialize, Deserialize, Clone, Debug, Default, PartialEq, Eq)]
+pub struct RegistrySyncManifest {
+    pub registries: BTreeMap<String, RegistrySyncEntries>,
+}
+
+/// The numeric ids of the items of a registry in a [`RegistrySyncManifest`]
+#[derive(Serialize, Deserialize, Clone, Debug, Default, PartialEq, Eq)]
+pub struct RegistrySyncEntries {
+    /// The ids of the items with a name, keyed by their names
+    pub names: BTreeMap<String, RegistryIdNumeric>,
+    /// The ids of the items without a name, keyed by their keys
+    pub keys: BTreeMap<String, RegistryIdNumeric>,
+}
+
+impl RegistrySyncManifest {
+    /// Creates a manifest of the current ids of every child registry
+    pub fn new() -> Self {
+        let registries = child_registries()
+            .into_iter()
+            .map(|(key, counter)| {
+                let counter = counter();
+                let mut counter = counter.lock();
+                let mut entries = RegistrySyncEntries::default();
+                for id in 0..counter.count() as RegistryIdNumeric {
+                    if let Some(name) = counter.name(id) {
+                        entries.names.insert(name.to_owned(), id);
+                    } else if let Some(key) = counter.key(id) {
+                        entries.keys.insert(key.to_owned(), id);
+                    }
+                }
+                (key.to_owned(), entries)
+            })
+            .collect();
+        Self { registries }
+    }
+
+    /// Builds the tables, that translate the ids of the server to the ids of the client.
+    /// Fails if the registries or items of the client and the server differ in either direction
+    pub fn sync(&self) -> Result<RegistrySync, RegistrySyncError> {
+        let local = child_registries();
+        let mut tables = BTreeMap::new();
+        let mut mismatches = vec![];
+        for (registry, entries) in self.registries.iter() {
+            let Some((key, counter)) = local.iter().find(|(key, _)| key == registry) else {
+                mismatches.push(RegistrySyncMismatch::MissingRegistry {
+                    registry: registry.clone(),
+                });
+                continue;
+            };
+            let counter = counter();
+            let mut counter = counter.lock();
+            let mut table = RegistrySyncTable::default();
+            for (name, &remote) in entries.names.iter() {
+                match counter.name_id(name) {
+                    Some(local) => table.insert(remote, local),
+                    None => mismatches.push(RegistrySyncMismatch::MissingItem {
+                        registry: registry.clone(),
+                        name: name.clone(),
+                    }),
+                }
+            }
+            for (key, &remote) in entries.keys.iter() {
+                match counter.numeric(key) {
+                    Some(local) => table.insert(remote, local),
+                    None => mismatches.push(RegistrySyncMismatch::MissingItem {
+                        registry: registry.clone(),
+                        name: key.clone(),
+                    }),
+                }
+            }
+            for local in 0..counter.count() as RegistryIdNumeric {
+                if table.to_remote.contains_key(&local) {
+                    continue;
+                }
+                let name = counter.name(local).or_else(|| counter.key(local));
+                mismatches.push(RegistrySyncMismatch::UnknownItem {
+                    registry: registry.clone(),
+                    name: name.unwrap_or_default().to_owned(),
+                });
+            }
+            tables.insert(*key, table);
+        }
+        for (key, _) in local.iter() {
+            if !self.registries.contains_key(*key) {
+                mismatches.push(RegistrySyncMismatch::UnknownRegistry {
+                    registry: (*key).to_owned(),
+                });
+            }
+        }
+        if mismatches.is_empty() {
+            Ok(RegistrySync { tables })
+        } else {
+            Err(RegistrySyncError { mismatches })
+        }
+    }
+
+    /// Writes the manifest as one line of JSON
+    pub fn send(&self, writer: impl Write) -> io::Result<()> {
+        send_line(self, writer)
+    }
+
+    /// Reads the manifest sent with [`RegistrySyncManifest::send`]
+    pub fn receive(reader: impl BufRead) -> io::Result<Self> {
+        receive_line(reader)
+    }
+}
+
+/// The answer of the client to the [`RegistrySyncManifest`]
+#[derive(Serialize, Deserialize, Clone, Debug, PartialEq, Eq)]
+pub enum RegistrySyncResponse {
+    Accepted,
+    Rejected(Vec<RegistrySyncMismatch>),
+}
+
+impl RegistrySyncResponse {
+    pub fn new(result: &Result<RegistrySync, RegistrySyncError>) -> Self {
+        match result {
+            Ok(_) => Self::Accepted,
+            Err(err) => Self::Rejected(err.mismatches.clone()),
+        }
+    }
+
+    /// Writes the response as one line of JSON
+    pub fn send(&self, writer: impl Write) -> io::Result<()> {
+        send_line(self, writer)
+    }
+
+    /// Reads the response sent with [`RegistrySyncResponse::send`]
+    pub fn receive(reader: impl BufRead) -> io::Result<Self> {
+        receive_line(reader)
+    }
+}
+
+fn send_line(value: &impl Serialize, mut writer: impl Write) -> io::Result<()> {
+    serde_json::to_writer(&mut writer, value)?;
+    writer.write_all(b"\n")?;
+    writer.flush()
+}
+
+fn receive_line<T: for<'de> Deserialize<'de>>(mut reader: impl BufRead) -> io::Result<T> {
+    let mut line = String::new();
+    if reader.read_line(&mut line)? == 0 {
+        return Err(io::ErrorKind::UnexpectedEof.into());
+    }
+    Ok(serde_json::from_str(&line)?)
+}
+
+/// Translates ids between the server and the client, is created by [`RegistrySyncManifest::sync`]
+#[derive(Resource, Clone, Debug, Default)]
+pub struct RegistrySync {
+    tables: BTreeMap<&'static str, RegistrySyncTable>,
+}
+
+impl RegistrySync {
+    /// Returns the id of the client, that has the numeric id on the server
+    pub fn to_local<R: ChildRegistry>(&self, remote: RegistryIdNumeric) -> Option<RegistryId<R>> {
+        let local = *self.table::<R>()?.to_local.get(remote as usize)?;
+        RegistryId::from_numeric(local?)
+    }
+
+    /// Returns the numeric id on the server of the id of the client
+    pub fn to_remote<R: ChildRegistry>(&self, local: RegistryId<R>) -> Option<RegistryIdNumeric> {
+        self.table::<R>()?.to_remote.get(&local.numeric()).copied()
+    }
+
+    fn table<R: ChildRegistry>(&self) -> Option<&RegistrySyncTable> {
+        self.tables.get(<R as RegistryItem>::KEY)
+    }
+}
+
+#[derive(Clone, Debug, Default)]
+struct RegistrySyncTable {
+    to_local: Vec<Option<RegistryIdNumeric>>,
+    to_remote: BTreeMap<RegistryIdNumeric, RegistryIdNumeric>,
+}
+
+impl RegistrySyncTable {
+    fn insert(&mut self, remote: RegistryIdNumeric, local: RegistryIdNumeric) {
+        let index = remote as usize;
+        if index >= self.to_local.len() {
+            self.to_local.resize(index + 1, None);
+        }
+        self.to_local[index] = Some(local);
+        self.to_remote.insert(local, remote);
+    }
+}
+
+/// A difference between the registries of the server and the client
+#[derive(Serialize, Deserialize, Clone, Debug, PartialEq, Eq)]
+pub enum RegistrySyncMismatch {
+    /// The server has the registry, but the client doesn't
+    MissingRegistry { registry: String },
+    /// The server has the item, but the client doesn't.
+    /// The name is the key of the item, if it has no name
+    MissingItem { registry: String, name: String },
+    /// The client has the registry, but the server doesn't
+    UnknownRegistry { registry: String },
+    /// The client has the item, but the server doesn't.
+    /// The name is the key of the item, if it has no name
+    UnknownItem { registry: String, name: String },
+}
+
+impl fmt::Display for RegistrySyncMismatch {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            Self::MissingRegistry { registry } => {
+                write!(f, "Registry {} is missing on the client", registry)
+            }
+            Self::MissingItem { registry, name } => {
+                write!(
+                    f,
+                    "Item {} of registry {} is missing on the client",
+                    name, registry
+                )
+            }
+            Self::UnknownRegistry { registry } => {
+                write!(f, "Registry {} is missing on the server", registry)
+            }
+            Self::UnknownItem { registry, name } => {
+                write!(
+                    f,
+                    "Item {} of registry {} is missing on the server",
+                    name, registry
+                )
+            }
+        }
+    }
+}
+
+/// The registries of the client don't match the registries of the server
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub struct RegistrySyncError {
+    pub mismatches: Vec<RegistrySyncMismatch>,
+}
+
+impl fmt::Display for RegistrySyncError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(f, "Registries can not be synchronized:")?;
+        for mismatch in self.mismatches.iter() {
+            write!(f, "\n{}", mismatch)?;
+        }
+        Ok(())
+    }
+}
+
+impl std::error::Error for RegistrySyncError {}
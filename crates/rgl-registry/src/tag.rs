@@ -0,0 +1,99 @@
+use std::marker::PhantomData;
+
+use bevy::ecs::system::Resource;
+
+use crate::{Registry, RegistryId, RegistryIdSet};
+
+/// A named group of items of one registry.
+/// Items are added to the tag using [`RegistryAppExt::add_to_tag`](crate::RegistryAppExt::add_to_tag),
+/// the members are stored in the [`RegistryTagSet`] resource
+pub trait RegistryTag: 'static + Sync + Send + Sized {
+    type Registry: Registry;
+}
+
+/// Something, that can tell if an id belongs to it
+pub trait RegistryIdFilter<R: Registry>: Sync + Send + 'static {
+    fn contains(&self, id: &RegistryId<R>) -> bool;
+}
+
+impl<R: Registry> RegistryIdFilter<R> for RegistryId<R> {
+    fn contains(&self, id: &RegistryId<R>) -> bool {
+        self.eq(id)
+    }
+}
+
+/// Filter of the item and its descendants, see [`RegistryId::is_a`]
+pub struct RegistryIsA<R: Registry>(pub RegistryId<R>);
+
+impl<R: Registry> RegistryIdFilter<R> for RegistryIsA<R> {
+    fn contains(&self, id: &RegistryId<R>) -> bool {
+        id.is_a(&self.0)
+    }
+}
+
+/// The members of the tag, they can be added only while the app is built,
+/// so the tag doesn't change after the registries are frozen, see [`RegistryPhase`](crate::RegistryPhase)
+#[derive(Resource)]
+pub struct RegistryTagSet<T: RegistryTag> {
+    members: RegistryIdSet<T::Registry>,
+    _marker: PhantomData<T>,
+}
+
+impl<T: RegistryTag> RegistryTagSet<T> {
+    /// Adds the id to the tag, returns true if it wasn't a member
+    pub(crate) fn insert(&mut self, id: RegistryId<T::Registry>) -> bool {
+        self.members.insert(id)
+    }
+
+    pub fn contains(&self, id: &RegistryId<T::Registry>) -> bool {
+        self.members.contains(id)
+    }
+
+    /// Returns true if the id or one of its ancestors is a member, see [`RegistryId::is_a`]
+    pub fn contains_inherited(&self, id: &RegistryId<T::Registry>) -> bool {
+        self.members.contains(id)
+            || id
+                .parent()
+                .is_some_and(|parent| self.contains_inherited(&parent))
+    }
+
+    pub fn iter(&self) -> impl Iterator<Item = RegistryId<T::Registry>> + '_ {
+        self.members.iter()
+    }
+
+    pub fn len(&self) -> usize {
+        self.members.len()
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.members.is_empty()
+    }
+
+    pub fn members(&self) -> &RegistryIdSet<T::Registry> {
+        &self.members
+    }
+}
+
+impl<T: RegistryTag> RegistryIdFilter<T::Registry> for RegistryTagSet<T> {
+    fn contains(&self, id: &RegistryId<T::Registry>) -> bool {
+        RegistryTagSet::contains(self, id)
+    }
+}
+
+impl<T: RegistryTag> Clone for RegistryTagSet<T> {
+    fn clone(&self) -> Self {
+        Self {
+            members: self.members.clone(),
+            _marker: PhantomData,
+        }
+    }
+}
+
+impl<T: RegistryTag> Default for RegistryTagSet<T> {
+    fn default() -> Self {
+        Self {
+            members: RegistryIdSet::new(),
+            _marker: PhantomData,
+        }
+    }
+}
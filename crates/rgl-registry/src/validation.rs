@@ -0,0 +1,120 @@
+use std::{any::type_name, fmt};
+
+use bevy::{log::error, prelude::*};
+
+use crate::{Registry, RegistryId, RegistryIdNumeric};
+
+/// An item of a registry, that has no value in a data cell
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub struct RegistryMissingEntry {
+    /// Type name of the registry
+    pub registry: &'static str,
+    /// Type name of the value of the data cell
+    pub value: &'static str,
+    pub id: RegistryIdNumeric,
+    /// Name of the item from the [`RegistryNameCell`](crate::RegistryNameCell)
+    pub name: Option<&'static str>,
+    /// Key of the item, that was used to assign the id
+    pub key: Option<&'static str>,
+}
+
+impl RegistryMissingEntry {
+    pub fn new<R: Registry, V>(id: RegistryIdNumeric) -> Self {
+        Self {
+            registry: type_name::<R>(),
+            value: type_name::<V>(),
+            id,
+            name: RegistryId::<R>::from_numeric(id).and_then(|id| id.name()),
+            key: R::scoped_counter().lock().key(id),
+        }
+    }
+}
+
+impl fmt::Display for RegistryMissingEntry {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(f, "Item {} ", self.id)?;
+        match (self.name, self.key) {
+            (Some(name), _) => write!(f, "({}) ", name)?,
+            (None, Some(key)) => write!(f, "({}) ", key)?,
+            (None, None) => {}
+        }
+        write!(
+            f,
+            "of registry {} has no value of type {}",
+            self.registry, self.value
+        )
+    }
+}
+
+/// The result of the conversion of every data cell.
+/// By default the missing entries are logged and the data cells with them are not inserted,
+/// [`RegistryAppExt::panic_on_invalid_registries`](crate::RegistryAppExt::panic_on_invalid_registries)
+/// makes the app panic instead
+#[derive(Resource, Clone, Debug, Default)]
+pub struct RegistryValidation {
+    pub missing: Vec<RegistryMissingEntry>,
+    /// Type names of the converted data cells, that were not inserted because of the missing entries
+    pub skipped: Vec<&'static str>,
+}
+
+impl RegistryValidation {
+    pub fn is_valid(&self) -> bool {
+        self.missing.is_empty()
+    }
+
+    pub fn result(&self) -> Result<(), RegistryValidationError> {
+        if self.is_valid() {
+            Ok(())
+        } else {
+            Err(RegistryValidationError {
+                missing: self.missing.clone(),
+                skipped: self.skipped.clone(),
+            })
+        }
+    }
+}
+
+#[derive(Clone, Debug)]
+pub struct RegistryValidationError {
+    pub missing: Vec<RegistryMissingEntry>,
+    /// See [`RegistryValidation::skipped`]
+    pub skipped: Vec<&'static str>,
+}
+
+impl fmt::Display for RegistryValidationError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(f, "{} registry entries are missing", self.missing.len())?;
+        for entry in self.missing.iter() {
+            write!(f, "\n{}", entry)?;
+        }
+        for cell in self.skipped.iter() {
+            write!(f, "\nData cell {} is not inserted", cell)?;
+        }
+        Ok(())
+    }
+}
+
+impl std::error::Error for RegistryValidationError {}
+
+/// If inserted, the app panics on the missing entries of [`RegistryValidation`] instead of logging them
+#[derive(Resource, Default)]
+pub(crate) struct RegistryValidationPanic;
+
+/// Logs the missing entries or panics after the data cells were converted
+pub(crate) fn check_validation(world: &World) {
+    let Err(err) = world.resource::<RegistryValidation>().result() else {
+        return;
+    };
+    if world.contains_resource::<RegistryValidationPanic>() {
+        panic!("{}", err);
+    }
+    for entry in err.missing.iter() {
+        error!("{}", entry);
+    }
+    for cell in err.skipped.iter() {
+        error!(
+            "Data cell {} is not inserted, because it has missing entries",
+            cell
+        );
+    }
+}
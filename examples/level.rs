@@ -2,7 +2,7 @@ use bevy::prelude::*;
 use bevy_ecs_tilemap::prelude::*;
 use rgl_level::{
     DefaultLevel, DefaultLevelObject, Layer, LayerBundle, LayerPlugin, Level, LevelBundle,
-    LevelObjectRarity, LevelPlugin,
+    LevelObjectRarity, LevelPlugin, LevelTilePattern,
 };
 use rgl_registry::*;
 
@@ -12,7 +12,7 @@ fn main() {
         .add_plugins(TilemapPlugin)
         .add_plugins(LevelPlugin)
         .add_plugins(LayerPlugin::<LevelTileRegistry>::default())
-        .register_two_sided_data_id2value::<LevelTileRegistry, &'static str>("level_tile")
+        .register_name::<LevelTileRegistry>("level_tile")
         .add_systems(Startup, setup)
         .run()
 }
@@ -33,8 +33,8 @@ fn setup(mut commands: Commands, asset_server: Res<AssetServer>) {
         Box::new(DefaultLevelObject::new(
             Some(RegistryId::new::<DefaultLevel>()),
             {
-                let mut tiles = [None; 9];
-                tiles[4] = Some(RegistryId::new::<Floor>());
+                let mut tiles: [Option<LevelTilePattern<_>>; 9] = Default::default();
+                tiles[4] = Some(RegistryId::new::<Floor>().into());
                 tiles
             },
             TileTextureIndex(0),
@@ -46,8 +46,8 @@ fn setup(mut commands: Commands, asset_server: Res<AssetServer>) {
         Box::new(DefaultLevelObject::new(
             Some(RegistryId::new::<DefaultLevel>()),
             {
-                let mut tiles = [None; 9];
-                tiles[4] = Some(RegistryId::new::<Wall>());
+                let mut tiles: [Option<LevelTilePattern<_>>; 9] = Default::default();
+                tiles[4] = Some(RegistryId::new::<Wall>().into());
                 tiles
             },
             TileTextureIndex(1),